@@ -0,0 +1,160 @@
+use crate::codec::schema_registry::{split_wire_format, SchemaCache, SchemaRegistry};
+use crate::codec::{CodecError, Decoded, MessageDecoder};
+use anyhow::Result;
+use apache_avro::reader::datum::GenericDatumReader;
+use apache_avro::Schema;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Writer schema with all schemas it references
+#[derive(Debug)]
+struct ParsedSchema {
+    schema: Schema,
+    references: Vec<Schema>,
+}
+
+/// Decodes Avro data in the Confluent wire format. Writer schemas are loaded
+/// from the Schema Registry by ID
+#[derive(Debug)]
+pub struct AvroDecoder {
+    schemas: SchemaCache<ParsedSchema>,
+}
+
+impl AvroDecoder {
+    pub const NAME: &'static str = "Avro";
+
+    pub fn new(registry: Arc<SchemaRegistry>) -> Self {
+        Self {
+            schemas: SchemaCache::new(registry, Self::load_schema),
+        }
+    }
+
+    fn load_schema(registry: &SchemaRegistry, id: u32) -> Result<ParsedSchema> {
+        let registered = registry.schema_by_id(id)?;
+        if registered.schema_type != "AVRO" {
+            return Err(CodecError::InvalidData(
+                Self::NAME.to_string(),
+                format!("schema {} has type {}", id, registered.schema_type),
+            )
+            .into());
+        }
+        let references = registry
+            .references(&registered)?
+            .into_iter()
+            .map(|(_, schema)| schema.schema.clone())
+            .collect::<Vec<String>>();
+        let (schema, references) = Schema::parse_str_with_list(&registered.schema, references)?;
+
+        Ok(ParsedSchema { schema, references })
+    }
+}
+
+impl MessageDecoder for AvroDecoder {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        let (id, mut body) = split_wire_format(data)?;
+        let parsed = self.schemas.get(id)?;
+        let value = GenericDatumReader::builder(&parsed.schema)
+            .writer_schemata(parsed.references.iter().collect())?
+            .build()?
+            .read_value(&mut body)?;
+        Ok(Decoded::Json(Value::try_from(value)?))
+    }
+
+    fn prefetch(&self, data: &[u8]) -> bool {
+        match split_wire_format(data) {
+            Ok((id, _)) => self.schemas.prefetch(id),
+            // Data without schema ID is not decoded
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::mock_registry::MockRegistry;
+    use crate::codec::schema_registry::SchemaRegistry;
+    use crate::codec::{AvroDecoder, Decoded, MessageDecoder};
+    use crate::kafka::SchemaRegistryConfig;
+    use apache_avro::types::Value;
+    use apache_avro::writer::datum::GenericDatumWriter;
+    use apache_avro::Schema;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const ADDRESS: &str = r#"{"type": "record", "name": "Address", "namespace": "test",
+        "fields": [{"name": "city", "type": "string"}]}"#;
+    const ORDER: &str = r#"{"type": "record", "name": "Order", "namespace": "test",
+        "fields": [{"name": "id", "type": "long"}, {"name": "address", "type": "Address"},
+                   {"name": "note", "type": ["null", "string"], "default": null}]}"#;
+
+    #[test]
+    fn test_avro_decode() {
+        let order_response = json!({
+            "schema": ORDER,
+            "references": [{"name": "test.Address", "subject": "address", "version": 1}]
+        })
+        .to_string();
+        let address_response = json!({"schema": ADDRESS}).to_string();
+        let registry = MockRegistry::start(vec![
+            ("/schemas/ids/5", order_response.as_str()),
+            ("/subjects/address/versions/1", address_response.as_str()),
+        ]);
+        let mut decoder = AvroDecoder::new(Arc::new(
+            SchemaRegistry::new(&SchemaRegistryConfig {
+                url: registry.url(),
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+        decoder.schemas.set_retry_after(Duration::ZERO);
+
+        let (schema, references) = Schema::parse_str_with_list(ORDER, [ADDRESS]).unwrap();
+        let record = Value::Record(vec![
+            ("id".to_string(), Value::Long(42)),
+            (
+                "address".to_string(),
+                Value::Record(vec![(
+                    "city".to_string(),
+                    Value::String("Riga".to_string()),
+                )]),
+            ),
+            (
+                "note".to_string(),
+                Value::Union(1, Box::new(Value::String("fast".to_string()))),
+            ),
+        ]);
+        let mut data = vec![0, 0, 0, 0, 5];
+        let writer = GenericDatumWriter::builder(&schema)
+            .schemata(references.iter().collect())
+            .unwrap()
+            .build()
+            .unwrap();
+        data.extend(writer.write_value_to_vec(record).unwrap());
+
+        registry.fail_next(1);
+        assert!(decoder.decode(&data).is_err());
+        // Lookups failed by server errors are repeated. Decoding waits for the schema
+        // loaded in the background
+        let expected =
+            Decoded::Json(json!({"id": 42, "address": {"city": "Riga"}, "note": "fast"}));
+        assert!(!decoder.prefetch(&data));
+        assert_eq!(expected, decoder.decode(&data).unwrap());
+        // Schemas are cached
+        assert!(decoder.prefetch(&data));
+        assert_eq!(expected, decoder.decode(&data).unwrap());
+        assert_eq!(3, registry.requests());
+
+        assert!(decoder.decode(&data[1..]).is_err());
+        data[4] = 6;
+        assert!(decoder.decode(&data).is_err());
+        // Lookups of schemas not registered are not repeated
+        let requests = registry.requests();
+        assert!(decoder.decode(&data).is_err());
+        assert_eq!(requests, registry.requests());
+    }
+}
@@ -0,0 +1,33 @@
+use crate::codec::{CodecError, Decoded, MessageDecoder};
+use anyhow::Result;
+use std::str::from_utf8;
+
+/// Keeps data as is
+#[derive(Debug)]
+pub struct BinaryDecoder;
+
+impl MessageDecoder for BinaryDecoder {
+    fn name(&self) -> &str {
+        "Binary"
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        Ok(Decoded::Binary(data.to_vec()))
+    }
+}
+
+/// Decodes data as UTF-8 text
+#[derive(Debug)]
+pub struct StringDecoder;
+
+impl MessageDecoder for StringDecoder {
+    fn name(&self) -> &str {
+        "String"
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        from_utf8(data)
+            .map(|text| Decoded::Text(text.to_string()))
+            .map_err(|e| CodecError::InvalidData(self.name().to_string(), e.to_string()).into())
+    }
+}
@@ -0,0 +1,216 @@
+use crate::codec::{CodecError, Decoded, MessageDecoder};
+use anyhow::Result;
+use bson::{Bson, Document};
+use serde_json::{Map, Number, Value};
+
+/// Binary values are shown as hex strings in documents
+pub(super) fn hex(data: &[u8]) -> Value {
+    Value::String(data.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn float(value: f64) -> Value {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+/// JSON object keys must be strings, other keys are written as JSON text
+fn object_key(key: Value) -> String {
+    match key {
+        Value::String(key) => key,
+        other => other.to_string(),
+    }
+}
+
+fn check_trailing(format: &str, rest: &[u8]) -> Result<()> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(CodecError::InvalidData(
+            format.to_string(),
+            format!("{} bytes after the value", rest.len()),
+        )
+        .into())
+    }
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(b) => Value::Bool(b),
+        rmpv::Value::Integer(i) => match (i.as_i64(), i.as_u64()) {
+            (Some(i), _) => Value::from(i),
+            (_, Some(u)) => Value::from(u),
+            _ => Value::String(i.to_string()),
+        },
+        rmpv::Value::F32(f) => float(f as f64),
+        rmpv::Value::F64(f) => float(f),
+        rmpv::Value::String(s) => match s.as_str() {
+            Some(s) => Value::String(s.to_string()),
+            None => hex(s.as_bytes()),
+        },
+        rmpv::Value::Binary(data) => hex(&data),
+        rmpv::Value::Array(items) => Value::Array(items.into_iter().map(msgpack_to_json).collect()),
+        rmpv::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (object_key(msgpack_to_json(k)), msgpack_to_json(v)))
+                .collect::<Map<String, Value>>(),
+        ),
+        rmpv::Value::Ext(kind, data) => {
+            let mut ext = Map::new();
+            ext.insert("ext".to_string(), Value::from(kind));
+            ext.insert("data".to_string(), hex(&data));
+            Value::Object(ext)
+        }
+    }
+}
+
+fn cbor_to_json(value: ciborium::Value) -> Value {
+    match value {
+        ciborium::Value::Null => Value::Null,
+        ciborium::Value::Bool(b) => Value::Bool(b),
+        ciborium::Value::Integer(i) => {
+            let i = i128::from(i);
+            match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(i), _) => Value::from(i),
+                (_, Ok(u)) => Value::from(u),
+                _ => Value::String(i.to_string()),
+            }
+        }
+        ciborium::Value::Float(f) => float(f),
+        ciborium::Value::Text(s) => Value::String(s),
+        ciborium::Value::Bytes(data) => hex(&data),
+        // Tags only give semantic to the value like date or URI, the value is shown as is
+        ciborium::Value::Tag(_, value) => cbor_to_json(*value),
+        ciborium::Value::Array(items) => {
+            Value::Array(items.into_iter().map(cbor_to_json).collect())
+        }
+        ciborium::Value::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (object_key(cbor_to_json(k)), cbor_to_json(v)))
+                .collect::<Map<String, Value>>(),
+        ),
+        _ => Value::Null,
+    }
+}
+
+/// Decodes single MessagePack value
+#[derive(Debug)]
+pub struct MessagePackDecoder;
+
+impl MessageDecoder for MessagePackDecoder {
+    fn name(&self) -> &str {
+        "MessagePack"
+    }
+
+    fn decode(&self, mut data: &[u8]) -> Result<Decoded> {
+        let value = rmpv::decode::read_value(&mut data)
+            .map_err(|e| CodecError::InvalidData(self.name().to_string(), e.to_string()))?;
+        check_trailing(self.name(), data)?;
+        Ok(Decoded::Json(msgpack_to_json(value)))
+    }
+}
+
+/// Decodes single CBOR data item
+#[derive(Debug)]
+pub struct CborDecoder;
+
+impl MessageDecoder for CborDecoder {
+    fn name(&self) -> &str {
+        "CBOR"
+    }
+
+    fn decode(&self, mut data: &[u8]) -> Result<Decoded> {
+        let value = ciborium::from_reader::<ciborium::Value, _>(&mut data)
+            .map_err(|e| CodecError::InvalidData(self.name().to_string(), e.to_string()))?;
+        check_trailing(self.name(), data)?;
+        Ok(Decoded::Json(cbor_to_json(value)))
+    }
+}
+
+/// Decodes BSON document. Types without JSON equivalent are shown in relaxed
+/// Extended JSON form, for example `{"$oid": "..."}`
+#[derive(Debug)]
+pub struct BsonDecoder;
+
+impl MessageDecoder for BsonDecoder {
+    fn name(&self) -> &str {
+        "BSON"
+    }
+
+    fn decode(&self, mut data: &[u8]) -> Result<Decoded> {
+        let document = Document::from_reader(&mut data)
+            .map_err(|e| CodecError::InvalidData(self.name().to_string(), e.to_string()))?;
+        check_trailing(self.name(), data)?;
+        Ok(Decoded::Json(
+            Bson::Document(document).into_relaxed_extjson(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{BsonDecoder, CborDecoder, Decoded, MessageDecoder, MessagePackDecoder};
+    use bson::doc;
+    use bson::oid::ObjectId;
+    use serde_json::json;
+
+    #[test]
+    fn test_msgpack() {
+        // {"id": 1, "name": "тест", "tags": ["a"], 7: bin [1, 2], "rate": 0.5}
+        let mut data = vec![
+            0x85, 0xa2, b'i', b'd', 0x01, 0xa4, b'n', b'a', b'm', b'e', 0xa8,
+        ];
+        data.extend("тест".as_bytes());
+        data.extend([0xa4, b't', b'a', b'g', b's', 0x91, 0xa1, b'a']);
+        data.extend([0x07, 0xc4, 0x02, 0x01, 0x02]);
+        data.extend([
+            0xa4, b'r', b'a', b't', b'e', 0xcb, 0x3f, 0xe0, 0, 0, 0, 0, 0, 0,
+        ]);
+
+        assert_eq!(
+            Decoded::Json(json!({
+                "id": 1, "name": "тест", "tags": ["a"], "7": "0102", "rate": 0.5
+            })),
+            MessagePackDecoder.decode(&data).unwrap()
+        );
+        assert!(MessagePackDecoder.decode(&data[..data.len() - 1]).is_err());
+        data.push(0xc0);
+        assert!(MessagePackDecoder.decode(&data).is_err());
+    }
+
+    #[test]
+    fn test_cbor() {
+        // {"id": -2, "bin": h'0a0b', "list": [1, 2.5, null, true]}
+        let data = [
+            0xa3, 0x62, b'i', b'd', 0x21, 0x63, b'b', b'i', b'n', 0x42, 0x0a, 0x0b, 0x64, b'l',
+            b'i', b's', b't', 0x84, 0x01, 0xf9, 0x41, 0x00, 0xf6, 0xf5,
+        ];
+        assert_eq!(
+            Decoded::Json(json!({"id": -2, "bin": "0a0b", "list": [1, 2.5, null, true]})),
+            CborDecoder.decode(&data).unwrap()
+        );
+        assert!(CborDecoder.decode(&data[..5]).is_err());
+    }
+
+    #[test]
+    fn test_bson() {
+        let id = ObjectId::parse_str("5f1a2b3c4d5e6f7a8b9c0d1e").unwrap();
+        let document = doc! {"_id": id, "name": "test", "count": 3_i64, "nested": {"ok": true}};
+        let mut data = vec![];
+        document.to_writer(&mut data).unwrap();
+
+        assert_eq!(
+            Decoded::Json(json!({
+                "_id": {"$oid": "5f1a2b3c4d5e6f7a8b9c0d1e"},
+                "name": "test",
+                "count": 3,
+                "nested": {"ok": true}
+            })),
+            BsonDecoder.decode(&data).unwrap()
+        );
+        assert!(BsonDecoder.decode(&data[1..]).is_err());
+    }
+}
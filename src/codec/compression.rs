@@ -0,0 +1,221 @@
+use crate::codec::{CodecError, Decoded, MessageDecoder};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+
+/// Maximum size of decompressed data, protects from compression bombs
+const MAX_DECOMPRESSED: usize = 64 * 1024 * 1024;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const LZ4_MAGIC: &[u8] = &[0x04, 0x22, 0x4d, 0x18];
+const SNAPPY_MAGIC: &[u8] = &[0xff, 0x06, 0x00, 0x00, b's', b'N', b'a', b'P', b'p', b'Y'];
+
+/// Compression applied by the producer to the payload itself, independently of
+/// Kafka batch compression
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    /// Compression is detected by magic bytes, unknown data is left as is
+    Auto,
+    Gzip,
+    Zstd,
+    /// LZ4 frame format
+    Lz4,
+    /// Snappy framed stream or raw block
+    Snappy,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compression::None => f.write_str("None"),
+            Compression::Auto => f.write_str("Auto"),
+            Compression::Gzip => f.write_str("gzip"),
+            Compression::Zstd => f.write_str("zstd"),
+            Compression::Lz4 => f.write_str("lz4"),
+            Compression::Snappy => f.write_str("snappy"),
+        }
+    }
+}
+
+fn read_limited<R: Read>(reader: R) -> std::io::Result<Vec<u8>> {
+    let mut data = vec![];
+    reader
+        .take(MAX_DECOMPRESSED as u64 + 1)
+        .read_to_end(&mut data)?;
+    if data.len() > MAX_DECOMPRESSED {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("decompressed data exceeds {} bytes", MAX_DECOMPRESSED),
+        ));
+    }
+    Ok(data)
+}
+
+impl Compression {
+    pub const ALL: [Compression; 6] = [
+        Compression::None,
+        Compression::Auto,
+        Compression::Gzip,
+        Compression::Zstd,
+        Compression::Lz4,
+        Compression::Snappy,
+    ];
+
+    /// Detects compression by magic bytes. Raw snappy blocks have no magic and are not detected
+    pub fn detect(data: &[u8]) -> Compression {
+        [
+            (GZIP_MAGIC, Compression::Gzip),
+            (ZSTD_MAGIC, Compression::Zstd),
+            (LZ4_MAGIC, Compression::Lz4),
+            (SNAPPY_MAGIC, Compression::Snappy),
+        ]
+        .into_iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, compression)| compression)
+        .unwrap_or(Compression::None)
+    }
+
+    pub fn decompress<'a>(&self, data: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        let result = match self {
+            Compression::None => return Ok(Cow::Borrowed(data)),
+            Compression::Auto => {
+                return match Compression::detect(data) {
+                    Compression::None => Ok(Cow::Borrowed(data)),
+                    detected => detected.decompress(data),
+                }
+            }
+            Compression::Gzip => read_limited(flate2::read::MultiGzDecoder::new(data)),
+            Compression::Zstd => zstd::stream::read::Decoder::new(data).and_then(read_limited),
+            Compression::Lz4 => read_limited(lz4_flex::frame::FrameDecoder::new(data)),
+            Compression::Snappy if data.starts_with(SNAPPY_MAGIC) => {
+                read_limited(snap::read::FrameDecoder::new(data))
+            }
+            Compression::Snappy => snap::raw::decompress_len(data)
+                .map_err(std::io::Error::from)
+                .and_then(|len| {
+                    if len > MAX_DECOMPRESSED {
+                        Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("decompressed data exceeds {} bytes", MAX_DECOMPRESSED),
+                        ))
+                    } else {
+                        snap::raw::Decoder::new()
+                            .decompress_vec(data)
+                            .map_err(std::io::Error::from)
+                    }
+                }),
+        };
+        result
+            .map(Cow::Owned)
+            .map_err(|e| CodecError::InvalidData(self.to_string(), e.to_string()).into())
+    }
+}
+
+/// Decode pipeline step: decompresses data and passes it to the format decoder
+#[derive(Debug)]
+pub struct Decompressing<'a> {
+    compression: Compression,
+    decoder: &'a dyn MessageDecoder,
+}
+
+impl<'a> Decompressing<'a> {
+    pub fn new(compression: Compression, decoder: &'a dyn MessageDecoder) -> Self {
+        Self {
+            compression,
+            decoder,
+        }
+    }
+}
+
+impl MessageDecoder for Decompressing<'_> {
+    fn name(&self) -> &str {
+        self.decoder.name()
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        self.decoder.decode(&self.compression.decompress(data)?)
+    }
+
+    fn prefetch(&self, data: &[u8]) -> bool {
+        match self.compression.decompress(data) {
+            Ok(data) => self.decoder.prefetch(&data),
+            // Decoding fails without the decoder
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{Compression, Decoded, Decompressing, JsonDecoder, MessageDecoder};
+    use serde_json::json;
+    use std::io::Write;
+
+    const PAYLOAD: &[u8] = br#"{"id": 1, "name": "test", "items": [1, 2, 3, 4, 5, 6, 7, 8]}"#;
+
+    fn compressed() -> Vec<(Compression, Vec<u8>)> {
+        let mut gzip = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        gzip.write_all(PAYLOAD).unwrap();
+
+        let mut lz4 = lz4_flex::frame::FrameEncoder::new(vec![]);
+        lz4.write_all(PAYLOAD).unwrap();
+
+        let mut snappy = snap::write::FrameEncoder::new(vec![]);
+        snappy.write_all(PAYLOAD).unwrap();
+
+        vec![
+            (Compression::Gzip, gzip.finish().unwrap()),
+            (Compression::Zstd, zstd::encode_all(PAYLOAD, 3).unwrap()),
+            (Compression::Lz4, lz4.finish().unwrap()),
+            (Compression::Snappy, snappy.into_inner().unwrap()),
+        ]
+    }
+
+    #[test]
+    fn test_decompress() {
+        for (compression, data) in compressed() {
+            assert_eq!(compression, Compression::detect(&data));
+            assert_eq!(PAYLOAD, compression.decompress(&data).unwrap().as_ref());
+            assert_eq!(
+                PAYLOAD,
+                Compression::Auto.decompress(&data).unwrap().as_ref()
+            );
+            assert!(compression.decompress(&data[..data.len() / 2]).is_err());
+        }
+
+        // Raw snappy block is not detected but can be selected
+        let raw = snap::raw::Encoder::new().compress_vec(PAYLOAD).unwrap();
+        assert_eq!(Compression::None, Compression::detect(&raw));
+        assert_eq!(
+            PAYLOAD,
+            Compression::Snappy.decompress(&raw).unwrap().as_ref()
+        );
+
+        assert_eq!(
+            PAYLOAD,
+            Compression::Auto.decompress(PAYLOAD).unwrap().as_ref()
+        );
+        assert_eq!(
+            PAYLOAD,
+            Compression::None.decompress(PAYLOAD).unwrap().as_ref()
+        );
+        assert!(Compression::Gzip.decompress(PAYLOAD).is_err());
+    }
+
+    #[test]
+    fn test_decompressing_decoder() {
+        let (_, data) = compressed().remove(0);
+        let decoder = Decompressing::new(Compression::Gzip, &JsonDecoder);
+        assert_eq!("JSON", decoder.name());
+        assert_eq!(
+            Decoded::Json(json!({"id": 1, "name": "test", "items": [1, 2, 3, 4, 5, 6, 7, 8]})),
+            decoder.decode(&data).unwrap()
+        );
+        assert!(JsonDecoder.decode(&data).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::codec::{CodecError, IntegerType, MessageEncoder};
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bson::Bson;
+use serde_json::Value;
+
+fn invalid(format: &str, reason: String) -> anyhow::Error {
+    CodecError::InvalidData(format.to_string(), reason).into()
+}
+
+/// Parses hex digits, whitespace between bytes is allowed
+fn parse_hex(format: &str, text: &str) -> Result<Vec<u8>> {
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<char>>();
+    if digits.len() % 2 != 0 {
+        return Err(invalid(format, "odd number of hex digits".to_string()));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let byte = pair.iter().collect::<String>();
+            u8::from_str_radix(&byte, 16)
+                .map_err(|_| invalid(format, format!("invalid hex byte {}", byte)))
+        })
+        .collect()
+}
+
+fn parse_json(format: &str, text: &str) -> Result<Value> {
+    serde_json::from_str(text).map_err(|e| invalid(format, e.to_string()))
+}
+
+/// Bytes entered as hex
+#[derive(Debug)]
+pub struct HexEncoder;
+
+impl MessageEncoder for HexEncoder {
+    fn name(&self) -> &str {
+        "Binary"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        parse_hex(self.name(), text)
+    }
+}
+
+/// UTF-8 text as is
+#[derive(Debug)]
+pub struct StringEncoder;
+
+impl MessageEncoder for StringEncoder {
+    fn name(&self) -> &str {
+        "String"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(text.as_bytes().to_vec())
+    }
+}
+
+/// Validated JSON document in compact form
+#[derive(Debug)]
+pub struct JsonEncoder;
+
+impl MessageEncoder for JsonEncoder {
+    fn name(&self) -> &str {
+        "JSON"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(parse_json(self.name(), text)?.to_string().into_bytes())
+    }
+}
+
+fn json_to_msgpack(value: Value) -> rmpv::Value {
+    match value {
+        Value::Null => rmpv::Value::Nil,
+        Value::Bool(b) => rmpv::Value::Boolean(b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => rmpv::Value::from(i),
+            (_, Some(u)) => rmpv::Value::from(u),
+            _ => rmpv::Value::F64(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => rmpv::Value::from(s),
+        Value::Array(items) => rmpv::Value::Array(items.into_iter().map(json_to_msgpack).collect()),
+        Value::Object(map) => rmpv::Value::Map(
+            map.into_iter()
+                .map(|(k, v)| (rmpv::Value::from(k), json_to_msgpack(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// JSON document written as MessagePack
+#[derive(Debug)]
+pub struct MessagePackEncoder;
+
+impl MessageEncoder for MessagePackEncoder {
+    fn name(&self) -> &str {
+        "MessagePack"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut data = vec![];
+        rmpv::encode::write_value(&mut data, &json_to_msgpack(parse_json(self.name(), text)?))?;
+        Ok(data)
+    }
+}
+
+/// JSON document written as CBOR
+#[derive(Debug)]
+pub struct CborEncoder;
+
+impl MessageEncoder for CborEncoder {
+    fn name(&self) -> &str {
+        "CBOR"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut data = vec![];
+        ciborium::into_writer(&parse_json(self.name(), text)?, &mut data)
+            .map_err(|e| invalid(self.name(), e.to_string()))?;
+        Ok(data)
+    }
+}
+
+/// JSON object written as BSON document. Extended JSON like `{"$oid": "..."}`
+/// is converted to BSON types
+#[derive(Debug)]
+pub struct BsonEncoder;
+
+impl MessageEncoder for BsonEncoder {
+    fn name(&self) -> &str {
+        "BSON"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let document = match Bson::try_from(parse_json(self.name(), text)?)? {
+            Bson::Document(document) => document,
+            _ => {
+                return Err(invalid(
+                    self.name(),
+                    "document must be an object".to_string(),
+                ))
+            }
+        };
+        let mut data = vec![];
+        document.to_writer(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Decimal integer written as fixed size bytes
+#[derive(Debug)]
+pub struct IntegerEncoder {
+    integer_type: IntegerType,
+    big_endian: bool,
+}
+
+impl IntegerEncoder {
+    pub fn new(integer_type: IntegerType, big_endian: bool) -> Self {
+        Self {
+            integer_type,
+            big_endian,
+        }
+    }
+}
+
+impl MessageEncoder for IntegerEncoder {
+    fn name(&self) -> &str {
+        match (self.integer_type, self.big_endian) {
+            (IntegerType::I32, true) => "Int32 BE",
+            (IntegerType::I32, false) => "Int32 LE",
+            (IntegerType::I64, true) => "Int64 BE",
+            (IntegerType::I64, false) => "Int64 LE",
+            (IntegerType::U64, true) => "UInt64 BE",
+            (IntegerType::U64, false) => "UInt64 LE",
+        }
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let text = text.trim();
+        let error = |e: std::num::ParseIntError| invalid(self.name(), e.to_string());
+        Ok(match (self.integer_type, self.big_endian) {
+            (IntegerType::I32, true) => text.parse::<i32>().map_err(error)?.to_be_bytes().to_vec(),
+            (IntegerType::I32, false) => text.parse::<i32>().map_err(error)?.to_le_bytes().to_vec(),
+            (IntegerType::I64, true) => text.parse::<i64>().map_err(error)?.to_be_bytes().to_vec(),
+            (IntegerType::I64, false) => text.parse::<i64>().map_err(error)?.to_le_bytes().to_vec(),
+            (IntegerType::U64, true) => text.parse::<u64>().map_err(error)?.to_be_bytes().to_vec(),
+            (IntegerType::U64, false) => text.parse::<u64>().map_err(error)?.to_le_bytes().to_vec(),
+        })
+    }
+}
+
+/// UUID in the hyphenated or simple form written as 16 bytes
+#[derive(Debug)]
+pub struct UuidEncoder;
+
+impl MessageEncoder for UuidEncoder {
+    fn name(&self) -> &str {
+        "UUID"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let data = parse_hex(self.name(), &text.trim().replace('-', ""))?;
+        if data.len() != 16 {
+            return Err(invalid(
+                self.name(),
+                format!("expected 16 bytes, got {}", data.len()),
+            ));
+        }
+        Ok(data)
+    }
+}
+
+/// Bytes entered as standard Base64
+#[derive(Debug)]
+pub struct Base64Encoder;
+
+impl MessageEncoder for Base64Encoder {
+    fn name(&self) -> &str {
+        "Base64"
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        STANDARD
+            .decode(text.trim())
+            .map_err(|e| invalid(self.name(), e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{
+        BsonDecoder, CborDecoder, Decoded, DecoderRegistry, EncoderRegistry, IntegerDecoder,
+        IntegerType, MessageDecoder, MessagePackDecoder,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let encoders = EncoderRegistry::new();
+        let decoders = DecoderRegistry::new();
+        let cases = [
+            ("Binary", "0a 0B ff", "0a 0B ff"),
+            ("String", "тест", "тест"),
+            (
+                "JSON",
+                "{\"b\": 1,\n \"a\": [true]}",
+                r#"{"b":1,"a":[true]}"#,
+            ),
+            ("Int32 BE", " -2 ", "-2"),
+            ("Int64 LE", "1700000000000", "1700000000000"),
+            ("UInt64 BE", "18446744073709551615", "18446744073709551615"),
+            (
+                "UUID",
+                "123e4567-e89b-12d3-a456-426614174000",
+                "123e4567-e89b-12d3-a456-426614174000",
+            ),
+            ("Base64", "AP9h", "AP9h"),
+        ];
+        for (name, text, expected) in cases {
+            let data = encoders.get(name).unwrap().encode(text).unwrap();
+            let decoded = decoders.get(name).decode(&data).unwrap();
+            match decoded {
+                Decoded::Binary(data) => assert_eq!(vec![0x0a, 0x0b, 0xff], data),
+                other => assert_eq!(expected, other.preview(100), "{}", name),
+            }
+        }
+
+        let document = r#"{"id": 1, "name": "test", "tags": ["a"], "rate": 0.5}"#;
+        let expected = Decoded::Json(json!({"id": 1, "name": "test", "tags": ["a"], "rate": 0.5}));
+        let formats: [(&str, &dyn MessageDecoder); 3] = [
+            ("MessagePack", &MessagePackDecoder),
+            ("CBOR", &CborDecoder),
+            ("BSON", &BsonDecoder),
+        ];
+        for (name, decoder) in formats {
+            let data = encoders.get(name).unwrap().encode(document).unwrap();
+            assert_eq!(expected, decoder.decode(&data).unwrap(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let encoders = EncoderRegistry::new();
+        for (name, text) in [
+            ("Binary", "0a0"),
+            ("Binary", "zz"),
+            ("JSON", "{\"id\": }"),
+            ("MessagePack", "[1,"),
+            ("BSON", "[1]"),
+            ("Int32 BE", "3000000000"),
+            ("UUID", "123e4567"),
+            ("Base64", "*"),
+        ] {
+            assert!(
+                encoders.get(name).unwrap().encode(text).is_err(),
+                "{}",
+                name
+            );
+        }
+        assert!(encoders.get("Session key").is_none());
+
+        let data = encoders.get("Int32 LE").unwrap().encode("513").unwrap();
+        assert_eq!(vec![1, 2, 0, 0], data);
+        assert_eq!(
+            Decoded::Text("513".to_string()),
+            IntegerDecoder::new(IntegerType::I32, false)
+                .decode(&data)
+                .unwrap()
+        );
+    }
+}
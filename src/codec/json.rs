@@ -0,0 +1,18 @@
+use crate::codec::{CodecError, Decoded, MessageDecoder};
+use anyhow::Result;
+
+/// Decodes data as JSON document
+#[derive(Debug)]
+pub struct JsonDecoder;
+
+impl MessageDecoder for JsonDecoder {
+    fn name(&self) -> &str {
+        "JSON"
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        serde_json::from_slice(data)
+            .map(Decoded::Json)
+            .map_err(|e| CodecError::InvalidData(self.name().to_string(), e.to_string()).into())
+    }
+}
@@ -0,0 +1,287 @@
+use crate::codec::compact::hex;
+use crate::codec::{CodecError, Decoded, MessageDecoder};
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde_json::{json, Value};
+use std::str::from_utf8;
+
+/// Size of timestamp in Kafka Streams window keys
+const TIMESTAMP_SIZE: usize = 8;
+
+/// Size of sequence number in keys of window store changelogs
+const SEQNUM_SIZE: usize = 4;
+
+fn invalid(format: &str, reason: String) -> anyhow::Error {
+    CodecError::InvalidData(format.to_string(), reason).into()
+}
+
+/// Returns data as array of exactly `N` bytes
+fn exact<const N: usize>(format: &str, data: &[u8]) -> Result<[u8; N]> {
+    data.try_into()
+        .map_err(|_| invalid(format, format!("expected {} bytes, got {}", N, data.len())))
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum IntegerType {
+    I32,
+    I64,
+    U64,
+}
+
+/// Decodes fixed size integers written by Kafka integer serializers (big-endian)
+/// or by native little-endian code
+#[derive(Debug)]
+pub struct IntegerDecoder {
+    integer_type: IntegerType,
+    big_endian: bool,
+}
+
+impl IntegerDecoder {
+    pub fn new(integer_type: IntegerType, big_endian: bool) -> Self {
+        Self {
+            integer_type,
+            big_endian,
+        }
+    }
+}
+
+impl MessageDecoder for IntegerDecoder {
+    fn name(&self) -> &str {
+        match (self.integer_type, self.big_endian) {
+            (IntegerType::I32, true) => "Int32 BE",
+            (IntegerType::I32, false) => "Int32 LE",
+            (IntegerType::I64, true) => "Int64 BE",
+            (IntegerType::I64, false) => "Int64 LE",
+            (IntegerType::U64, true) => "UInt64 BE",
+            (IntegerType::U64, false) => "UInt64 LE",
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        let name = self.name();
+        let text = match (self.integer_type, self.big_endian) {
+            (IntegerType::I32, true) => i32::from_be_bytes(exact(name, data)?).to_string(),
+            (IntegerType::I32, false) => i32::from_le_bytes(exact(name, data)?).to_string(),
+            (IntegerType::I64, true) => i64::from_be_bytes(exact(name, data)?).to_string(),
+            (IntegerType::I64, false) => i64::from_le_bytes(exact(name, data)?).to_string(),
+            (IntegerType::U64, true) => u64::from_be_bytes(exact(name, data)?).to_string(),
+            (IntegerType::U64, false) => u64::from_le_bytes(exact(name, data)?).to_string(),
+        };
+        Ok(Decoded::Text(text))
+    }
+}
+
+/// Decodes 16 bytes UUID into the standard hyphenated form
+#[derive(Debug)]
+pub struct UuidDecoder;
+
+impl MessageDecoder for UuidDecoder {
+    fn name(&self) -> &str {
+        "UUID"
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        let bytes: [u8; 16] = exact(self.name(), data)?;
+        let hex = bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        Ok(Decoded::Text(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )))
+    }
+}
+
+/// Shows data as standard Base64 text
+#[derive(Debug)]
+pub struct Base64Decoder;
+
+impl MessageDecoder for Base64Decoder {
+    fn name(&self) -> &str {
+        "Base64"
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        Ok(Decoded::Text(STANDARD.encode(data)))
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum WindowType {
+    /// Key of time window: inner key and window start. Window end is not stored,
+    /// it depends on the window size
+    Time,
+    /// Key of window store changelog: inner key, window start and sequence number
+    TimeChangelog,
+    /// Key of session window: inner key, window end and window start
+    Session,
+}
+
+/// Decodes keys of Kafka Streams windowed aggregations. Inner key is shown as text
+/// when it is valid UTF-8 and as hex otherwise, window bounds as UTC time
+#[derive(Debug)]
+pub struct WindowedKeyDecoder {
+    window_type: WindowType,
+}
+
+impl WindowedKeyDecoder {
+    pub fn new(window_type: WindowType) -> Self {
+        Self { window_type }
+    }
+}
+
+fn timestamp(data: &[u8]) -> Value {
+    let millis = i64::from_be_bytes(data.try_into().unwrap_or_default());
+    match Utc.timestamp_millis_opt(millis).single() {
+        Some(time) => Value::String(time.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        None => Value::from(millis),
+    }
+}
+
+impl MessageDecoder for WindowedKeyDecoder {
+    fn name(&self) -> &str {
+        match self.window_type {
+            WindowType::Time => "Windowed key",
+            WindowType::TimeChangelog => "Windowed key (changelog)",
+            WindowType::Session => "Session key",
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        let suffix = match self.window_type {
+            WindowType::Time => TIMESTAMP_SIZE,
+            WindowType::TimeChangelog => TIMESTAMP_SIZE + SEQNUM_SIZE,
+            WindowType::Session => 2 * TIMESTAMP_SIZE,
+        };
+        if data.len() < suffix {
+            return Err(invalid(
+                self.name(),
+                format!("expected at least {} bytes, got {}", suffix, data.len()),
+            ));
+        }
+
+        let (key, window) = data.split_at(data.len() - suffix);
+        let key = match from_utf8(key) {
+            Ok(key) => Value::String(key.to_string()),
+            Err(_) => hex(key),
+        };
+        let value = match self.window_type {
+            WindowType::Time => json!({"key": key, "start": timestamp(window)}),
+            WindowType::TimeChangelog => json!({
+                "key": key,
+                "start": timestamp(&window[..TIMESTAMP_SIZE]),
+                "seq": u32::from_be_bytes(exact(self.name(), &window[TIMESTAMP_SIZE..])?),
+            }),
+            WindowType::Session => json!({
+                "key": key,
+                "start": timestamp(&window[TIMESTAMP_SIZE..]),
+                "end": timestamp(&window[..TIMESTAMP_SIZE]),
+            }),
+        };
+        Ok(Decoded::Json(value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{
+        Base64Decoder, Decoded, IntegerDecoder, IntegerType, MessageDecoder, UuidDecoder,
+        WindowType, WindowedKeyDecoder,
+    };
+    use serde_json::json;
+
+    fn text(decoder: &dyn MessageDecoder, data: &[u8]) -> String {
+        match decoder.decode(data).unwrap() {
+            Decoded::Text(text) => text,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_integer_keys() {
+        let i32_be = IntegerDecoder::new(IntegerType::I32, true);
+        assert_eq!("-2", text(&i32_be, &[0xff, 0xff, 0xff, 0xfe]));
+        assert_eq!("258", text(&i32_be, &[0, 0, 1, 2]));
+        assert!(i32_be.decode(&[0, 1, 2]).is_err());
+
+        let i32_le = IntegerDecoder::new(IntegerType::I32, false);
+        assert_eq!("513", text(&i32_le, &[1, 2, 0, 0]));
+
+        let data = 1_700_000_000_000_i64.to_be_bytes();
+        let i64_be = IntegerDecoder::new(IntegerType::I64, true);
+        assert_eq!("1700000000000", text(&i64_be, &data));
+        assert!(i64_be.decode(&data[..4]).is_err());
+        let i64_le = IntegerDecoder::new(IntegerType::I64, false);
+        assert_eq!("-1", text(&i64_le, &[0xff; 8]));
+
+        let u64_be = IntegerDecoder::new(IntegerType::U64, true);
+        assert_eq!(u64::MAX.to_string(), text(&u64_be, &[0xff; 8]));
+        let u64_le = IntegerDecoder::new(IntegerType::U64, false);
+        assert_eq!("1", text(&u64_le, &[1, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!("UInt64 LE", u64_le.name());
+    }
+
+    #[test]
+    fn test_uuid_and_base64_keys() {
+        let data = [
+            0x12, 0x3e, 0x45, 0x67, 0xe8, 0x9b, 0x12, 0xd3, 0xa4, 0x56, 0x42, 0x66, 0x14, 0x17,
+            0x40, 0x00,
+        ];
+        assert_eq!(
+            "123e4567-e89b-12d3-a456-426614174000",
+            text(&UuidDecoder, &data)
+        );
+        assert!(UuidDecoder.decode(&data[1..]).is_err());
+
+        assert_eq!("AP9h", text(&Base64Decoder, &[0x00, 0xff, 0x61]));
+    }
+
+    #[test]
+    fn test_windowed_keys() {
+        let start = 1_700_000_000_000_i64.to_be_bytes();
+        let end = 1_700_000_060_000_i64.to_be_bytes();
+
+        let mut data = b"user-1".to_vec();
+        data.extend(start);
+        assert_eq!(
+            Decoded::Json(json!({"key": "user-1", "start": "2023-11-14T22:13:20.000Z"})),
+            WindowedKeyDecoder::new(WindowType::Time)
+                .decode(&data)
+                .unwrap()
+        );
+
+        data.extend(7_u32.to_be_bytes());
+        assert_eq!(
+            Decoded::Json(json!({
+                "key": "user-1", "start": "2023-11-14T22:13:20.000Z", "seq": 7
+            })),
+            WindowedKeyDecoder::new(WindowType::TimeChangelog)
+                .decode(&data)
+                .unwrap()
+        );
+
+        let mut data = vec![0xff, 0x01];
+        data.extend(end);
+        data.extend(start);
+        assert_eq!(
+            Decoded::Json(json!({
+                "key": "ff01",
+                "start": "2023-11-14T22:13:20.000Z",
+                "end": "2023-11-14T22:14:20.000Z"
+            })),
+            WindowedKeyDecoder::new(WindowType::Session)
+                .decode(&data)
+                .unwrap()
+        );
+        assert!(WindowedKeyDecoder::new(WindowType::Session)
+            .decode(&data[..10])
+            .is_err());
+    }
+}
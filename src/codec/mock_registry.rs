@@ -0,0 +1,106 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Debug, Default)]
+struct Requests {
+    count: usize,
+    last_authorization: Option<String>,
+    /// Number of the next requests answered with 503 error
+    failures: usize,
+}
+
+/// Schema Registry HTTP server for tests. Responds to GET requests with
+/// predefined JSON bodies, unknown paths get 404 error
+pub struct MockRegistry {
+    port: u16,
+    requests: Arc<Mutex<Requests>>,
+}
+
+impl MockRegistry {
+    pub fn start(responses: Vec<(&str, &str)>) -> Self {
+        let responses = responses
+            .into_iter()
+            .map(|(path, body)| (path.to_string(), body.to_string()))
+            .collect::<Vec<(String, String)>>();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Requests::default()));
+
+        let log = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => break,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                let mut authorization = None;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("authorization") {
+                            authorization = Some(value.trim().to_string());
+                        }
+                    }
+                }
+                let unavailable = {
+                    let mut log = log.lock().unwrap();
+                    log.count += 1;
+                    log.last_authorization = authorization;
+                    let unavailable = log.failures > 0;
+                    log.failures = log.failures.saturating_sub(1);
+                    unavailable
+                };
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("");
+                let (status, body) = match responses.iter().find(|(p, _)| p == path) {
+                    _ if unavailable => (
+                        "503 Service Unavailable",
+                        r#"{"error_code": 50003, "message": "Unavailable"}"#.to_string(),
+                    ),
+                    Some((_, body)) => ("200 OK", body.clone()),
+                    None => (
+                        "404 Not Found",
+                        r#"{"error_code": 40403, "message": "Schema not found"}"#.to_string(),
+                    ),
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        Self { port, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// Number of requests received by the server
+    pub fn requests(&self) -> usize {
+        self.requests.lock().unwrap().count
+    }
+
+    pub fn last_authorization(&self) -> Option<String> {
+        self.requests.lock().unwrap().last_authorization.clone()
+    }
+
+    /// Answers the next `count` requests with 503 error
+    pub fn fail_next(&self, count: usize) {
+        self.requests.lock().unwrap().failures = count;
+    }
+}
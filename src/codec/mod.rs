@@ -0,0 +1,309 @@
+mod avro;
+mod basic;
+mod compact;
+mod compression;
+mod encode;
+mod json;
+mod keys;
+#[cfg(test)]
+mod mock_registry;
+mod protobuf;
+mod schema_registry;
+
+pub use avro::AvroDecoder;
+pub use basic::{BinaryDecoder, StringDecoder};
+pub use compact::{BsonDecoder, CborDecoder, MessagePackDecoder};
+pub use compression::{Compression, Decompressing};
+pub use encode::{
+    Base64Encoder, BsonEncoder, CborEncoder, HexEncoder, IntegerEncoder, JsonEncoder,
+    MessagePackEncoder, StringEncoder, UuidEncoder,
+};
+pub use json::JsonDecoder;
+pub use keys::{
+    Base64Decoder, IntegerDecoder, IntegerType, UuidDecoder, WindowType, WindowedKeyDecoder,
+};
+pub use protobuf::{
+    load_descriptors, message_names, ProtobufDecoder, ProtobufEncoder, RegistryProtobufDecoder,
+};
+pub use schema_registry::{RegisteredSchema, SchemaRegistry};
+
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt::Debug;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("invalid {0} data: {1}")]
+    InvalidData(String, String),
+    #[error("schema registry error {0}: {1}")]
+    Registry(u16, String),
+    #[error("message type {0} not found")]
+    UnknownMessage(String),
+}
+
+/// Result of message key or payload decoding
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decoded {
+    /// Bytes without known structure
+    Binary(Vec<u8>),
+    /// Text
+    Text(String),
+    /// Structured document
+    Json(Value),
+}
+
+impl Decoded {
+    /// Single line representation limited to `max_chars` characters. Documents are
+    /// shown in compact form, binary data as hex
+    pub fn preview(&self, max_chars: usize) -> String {
+        match self {
+            Decoded::Binary(data) => hex_preview(data, max_chars / 3),
+            Decoded::Text(text) => text_preview(text, max_chars),
+            Decoded::Json(value) => text_preview(&value.to_string(), max_chars),
+        }
+    }
+
+    /// Document for JSON path filters. Text and binary data are parsed as JSON
+    pub fn into_json(self) -> Option<Value> {
+        match self {
+            Decoded::Binary(data) => serde_json::from_slice(&data).ok(),
+            Decoded::Text(text) => serde_json::from_str(&text).ok(),
+            Decoded::Json(value) => Some(value),
+        }
+    }
+}
+
+/// Single line text limited to `max_chars` characters, control characters are replaced
+/// by spaces
+pub fn text_preview(text: &str, max_chars: usize) -> String {
+    let mut preview = text
+        .chars()
+        .take(max_chars)
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect::<String>();
+    if text.chars().nth(max_chars).is_some() {
+        preview.push_str("...");
+    }
+    preview
+}
+
+/// Formats first `max_bytes` bytes of data as hex in a single line
+pub fn hex_preview(data: &[u8], max_bytes: usize) -> String {
+    let mut preview = data
+        .iter()
+        .take(max_bytes)
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(" ");
+    if data.len() > max_bytes {
+        preview.push_str(" ...");
+    }
+    preview
+}
+
+/// Decoder of message keys and payloads. Decoders are selected by name
+/// in topic preferences
+pub trait MessageDecoder: Debug {
+    /// Unique name of the decoder, it is stored in topic preferences
+    fn name(&self) -> &str;
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded>;
+
+    /// Starts loading what decoding of the data needs, e.g. the writer schema, in the
+    /// background. Returns false while it is loading, decode blocks until it is loaded
+    fn prefetch(&self, _data: &[u8]) -> bool {
+        true
+    }
+}
+
+/// Named decoders available for topics
+#[derive(Debug)]
+pub struct DecoderRegistry {
+    /// The first decoder is used for unknown names
+    decoders: Vec<Box<dyn MessageDecoder>>,
+}
+
+impl DecoderRegistry {
+    /// Creates registry with built-in decoders
+    pub fn new() -> Self {
+        let mut registry = Self { decoders: vec![] };
+        registry.register(Box::new(BinaryDecoder));
+        registry.register(Box::new(StringDecoder));
+        registry.register(Box::new(JsonDecoder));
+        registry.register(Box::new(MessagePackDecoder));
+        registry.register(Box::new(CborDecoder));
+        registry.register(Box::new(BsonDecoder));
+        for integer_type in [IntegerType::I32, IntegerType::I64, IntegerType::U64] {
+            registry.register(Box::new(IntegerDecoder::new(integer_type, true)));
+            registry.register(Box::new(IntegerDecoder::new(integer_type, false)));
+        }
+        registry.register(Box::new(UuidDecoder));
+        registry.register(Box::new(Base64Decoder));
+        registry.register(Box::new(WindowedKeyDecoder::new(WindowType::Time)));
+        registry.register(Box::new(WindowedKeyDecoder::new(WindowType::TimeChangelog)));
+        registry.register(Box::new(WindowedKeyDecoder::new(WindowType::Session)));
+        registry
+    }
+
+    /// Adds decoder to the registry. Decoder with the same name is replaced
+    pub fn register(&mut self, decoder: Box<dyn MessageDecoder>) {
+        match self
+            .decoders
+            .iter_mut()
+            .find(|d| d.name() == decoder.name())
+        {
+            Some(existing) => *existing = decoder,
+            None => self.decoders.push(decoder),
+        }
+    }
+
+    /// Names of registered decoders in order of registration
+    pub fn names(&self) -> Vec<&str> {
+        self.decoders.iter().map(|d| d.name()).collect()
+    }
+
+    /// Removes decoder with the given name
+    pub fn remove(&mut self, name: &str) {
+        self.decoders.retain(|d| d.name() != name);
+    }
+
+    /// Returns decoder with the given name. Binary decoder is returned for unknown
+    /// names, so preferences stored with removed decoders still show data
+    pub fn get<T: AsRef<str>>(&self, name: T) -> &dyn MessageDecoder {
+        self.decoders
+            .iter()
+            .find(|d| d.name() == name.as_ref())
+            .unwrap_or(&self.decoders[0])
+            .as_ref()
+    }
+}
+
+/// Encoder of message keys and payloads entered as text. Encoders have the same
+/// names as decoders of the same format. They are shared with bulk produce workers
+pub trait MessageEncoder: Debug + Send + Sync {
+    fn name(&self) -> &str;
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>>;
+}
+
+/// Named encoders available for producing messages
+#[derive(Debug)]
+pub struct EncoderRegistry {
+    encoders: Vec<Arc<dyn MessageEncoder>>,
+}
+
+impl EncoderRegistry {
+    /// Creates registry with built-in encoders
+    pub fn new() -> Self {
+        let mut registry = Self { encoders: vec![] };
+        registry.register(Box::new(HexEncoder));
+        registry.register(Box::new(StringEncoder));
+        registry.register(Box::new(JsonEncoder));
+        registry.register(Box::new(MessagePackEncoder));
+        registry.register(Box::new(CborEncoder));
+        registry.register(Box::new(BsonEncoder));
+        for integer_type in [IntegerType::I32, IntegerType::I64, IntegerType::U64] {
+            registry.register(Box::new(IntegerEncoder::new(integer_type, true)));
+            registry.register(Box::new(IntegerEncoder::new(integer_type, false)));
+        }
+        registry.register(Box::new(UuidEncoder));
+        registry.register(Box::new(Base64Encoder));
+        registry
+    }
+
+    /// Adds encoder to the registry. Encoder with the same name is replaced
+    pub fn register(&mut self, encoder: Box<dyn MessageEncoder>) {
+        match self
+            .encoders
+            .iter_mut()
+            .find(|e| e.name() == encoder.name())
+        {
+            Some(existing) => *existing = encoder.into(),
+            None => self.encoders.push(encoder.into()),
+        }
+    }
+
+    /// Names of registered encoders in order of registration
+    pub fn names(&self) -> Vec<&str> {
+        self.encoders.iter().map(|e| e.name()).collect()
+    }
+
+    /// Removes encoder with the given name
+    pub fn remove(&mut self, name: &str) {
+        self.encoders.retain(|e| e.name() != name);
+    }
+
+    /// Returns encoder with the given name. Formats without encoder like windowed
+    /// keys return `None`
+    pub fn get<T: AsRef<str>>(&self, name: T) -> Option<&dyn MessageEncoder> {
+        self.encoders
+            .iter()
+            .find(|e| e.name() == name.as_ref())
+            .map(|e| e.as_ref())
+    }
+
+    /// Returns encoder with the given name which can be moved to another thread
+    pub fn shared<T: AsRef<str>>(&self, name: T) -> Option<Arc<dyn MessageEncoder>> {
+        self.encoders
+            .iter()
+            .find(|e| e.name() == name.as_ref())
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{Decoded, DecoderRegistry, StringDecoder};
+    use serde_json::json;
+
+    #[test]
+    fn test_registry() {
+        let mut registry = DecoderRegistry::new();
+        let count = registry.names().len();
+        assert_eq!(vec!["Binary", "String", "JSON"], registry.names()[..3]);
+        assert!(registry.names().contains(&"Session key"));
+
+        registry.register(Box::new(StringDecoder));
+        assert_eq!(count, registry.names().len());
+        assert_eq!("String", registry.names()[1]);
+
+        let data = "тест".as_bytes();
+        assert_eq!(
+            Decoded::Text("тест".to_string()),
+            registry.get("String").decode(data).unwrap()
+        );
+        assert_eq!(
+            Decoded::Binary(data.to_vec()),
+            registry.get("Unknown").decode(data).unwrap()
+        );
+        assert!(registry.get("String").decode(&[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let registry = DecoderRegistry::new();
+        let decoded = registry
+            .get("JSON")
+            .decode(r#"{"id": 1, "name": "тест", "tags": ["a", "b"]}"#.as_bytes())
+            .unwrap();
+        assert_eq!(
+            Decoded::Json(json!({"id": 1, "name": "тест", "tags": ["a", "b"]})),
+            decoded
+        );
+        assert_eq!(
+            r#"{"id":1,"name":"тест","tags":["a","b"]}"#,
+            decoded.preview(100)
+        );
+        assert!(registry.get("JSON").decode(b"{\"id\":").is_err());
+    }
+
+    #[test]
+    fn test_preview() {
+        // Multi-byte characters must not be split
+        let text = Decoded::Text("ж\nж".repeat(50));
+        assert_eq!(format!("{}...", "ж ж".repeat(33) + "ж"), text.preview(100));
+        assert_eq!("01 02 ...", Decoded::Binary(vec![1, 2, 3]).preview(6));
+    }
+}
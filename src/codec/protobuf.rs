@@ -0,0 +1,385 @@
+use crate::codec::schema_registry::{split_wire_format, SchemaCache, SchemaRegistry};
+use crate::codec::{CodecError, Decoded, MessageDecoder, MessageEncoder};
+use anyhow::Result;
+use prost_reflect::prost::encoding::decode_varint;
+use prost_reflect::prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, SerializeOptions};
+use protox::file::{ChainFileResolver, File, FileResolver, GoogleFileResolver};
+use protox::Compiler;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Proto field names and all fields including defaults are easier to compare with the sources
+const JSON_OPTIONS: SerializeOptions = SerializeOptions::new()
+    .use_proto_field_name(true)
+    .skip_default_fields(false)
+    .stringify_64_bit_integers(false);
+
+/// Loads message descriptors from `.proto` source or compiled `FileDescriptorSet`.
+/// Imports of `.proto` files are resolved relative to the file directory
+pub fn load_descriptors<P: AsRef<Path>>(path: P) -> Result<DescriptorPool> {
+    let path = path.as_ref();
+    if path.extension().map(|ext| ext == "proto").unwrap_or(false) {
+        let include = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut compiler = Compiler::new([include])?;
+        compiler.open_file(path)?;
+        Ok(compiler.descriptor_pool())
+    } else {
+        Ok(DescriptorPool::decode(fs::read(path)?.as_slice())?)
+    }
+}
+
+/// Full names of all message types of the pool except generated map entries
+pub fn message_names(pool: &DescriptorPool) -> Vec<String> {
+    pool.all_messages()
+        .filter(|m| !m.is_map_entry() && !m.full_name().starts_with("google.protobuf."))
+        .map(|m| m.full_name().to_string())
+        .collect()
+}
+
+fn to_json(message: MessageDescriptor, data: &[u8]) -> Result<Decoded> {
+    let message = DynamicMessage::decode(message, data)?;
+    Ok(Decoded::Json(message.serialize_with_options(
+        serde_json::value::Serializer,
+        &JSON_OPTIONS,
+    )?))
+}
+
+/// Reads message indexes which follow schema ID in the Confluent wire format. Indexes
+/// are the path to the message type in the schema: top level message, nested message and so on
+fn split_message_indexes(mut data: &[u8]) -> Result<(Vec<usize>, &[u8])> {
+    let mut read = || -> Result<usize> {
+        let value = decode_varint(&mut data)?;
+        // zigzag encoding
+        let value = (value >> 1) as i64 ^ -((value & 1) as i64);
+        usize::try_from(value).map_err(|_| {
+            CodecError::InvalidData("Protobuf".to_string(), "negative message index".to_string())
+                .into()
+        })
+    };
+
+    let count = read()?;
+    let indexes = match count {
+        // The first message is written as single 0
+        0 => vec![0],
+        _ => (0..count).map(|_| read()).collect::<Result<Vec<usize>>>()?,
+    };
+    Ok((indexes, data))
+}
+
+/// Decodes protobuf messages of the type selected for the topic. Data in the Confluent
+/// wire format is accepted too, the header is skipped. Plain protobuf message never
+/// starts with zero byte because field number 0 is not allowed
+#[derive(Debug)]
+pub struct ProtobufDecoder {
+    message: MessageDescriptor,
+}
+
+impl ProtobufDecoder {
+    pub const NAME: &'static str = "Protobuf";
+
+    pub fn new(message: MessageDescriptor) -> Self {
+        Self { message }
+    }
+
+    /// Creates decoder for the message type `message_name` of the pool
+    pub fn from_pool(pool: &DescriptorPool, message_name: &str) -> Result<Self> {
+        match pool.get_message_by_name(message_name) {
+            Some(message) => Ok(Self::new(message)),
+            None => Err(CodecError::UnknownMessage(message_name.to_string()).into()),
+        }
+    }
+}
+
+impl MessageDecoder for ProtobufDecoder {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        let body = match split_wire_format(data) {
+            Ok((_, body)) => split_message_indexes(body)?.1,
+            Err(_) => data,
+        };
+        to_json(self.message.clone(), body)
+    }
+}
+
+/// Encodes JSON documents as protobuf messages of the type selected for the topic.
+/// Fields are accepted by proto and JSON names
+#[derive(Debug)]
+pub struct ProtobufEncoder {
+    message: MessageDescriptor,
+}
+
+impl ProtobufEncoder {
+    pub fn new(message: MessageDescriptor) -> Self {
+        Self { message }
+    }
+}
+
+impl MessageEncoder for ProtobufEncoder {
+    fn name(&self) -> &str {
+        ProtobufDecoder::NAME
+    }
+
+    fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut deserializer = serde_json::Deserializer::from_str(text);
+        let message = DynamicMessage::deserialize(self.message.clone(), &mut deserializer)
+            .and_then(|message| deserializer.end().map(|_| message))
+            .map_err(|e| CodecError::InvalidData(self.name().to_string(), e.to_string()))?;
+        Ok(message.encode_to_vec())
+    }
+}
+
+/// Schemas with their references, the registry refers them by import name
+struct RegistryFiles {
+    files: HashMap<String, String>,
+}
+
+impl FileResolver for RegistryFiles {
+    fn open_file(&self, name: &str) -> Result<File, protox::Error> {
+        match self.files.get(name) {
+            Some(source) => File::from_source(name, source),
+            None => Err(protox::Error::file_not_found(name)),
+        }
+    }
+}
+
+/// Compiled schema registered in the Schema Registry
+#[derive(Debug)]
+struct ParsedSchema {
+    pool: DescriptorPool,
+    file: String,
+}
+
+/// Decodes protobuf data in the Confluent wire format. Schemas are loaded from the Schema
+/// Registry by ID and the message type is selected by message indexes
+#[derive(Debug)]
+pub struct RegistryProtobufDecoder {
+    schemas: SchemaCache<ParsedSchema>,
+}
+
+impl RegistryProtobufDecoder {
+    pub const NAME: &'static str = "Protobuf (registry)";
+
+    pub fn new(registry: Arc<SchemaRegistry>) -> Self {
+        Self {
+            schemas: SchemaCache::new(registry, Self::load_schema),
+        }
+    }
+
+    fn load_schema(registry: &SchemaRegistry, id: u32) -> Result<ParsedSchema> {
+        let registered = registry.schema_by_id(id)?;
+        if registered.schema_type != "PROTOBUF" {
+            return Err(CodecError::InvalidData(
+                Self::NAME.to_string(),
+                format!("schema {} has type {}", id, registered.schema_type),
+            )
+            .into());
+        }
+        let file = format!("schema_{}.proto", id);
+        let mut files = registry
+            .references(&registered)?
+            .into_iter()
+            .map(|(name, schema)| (name, schema.schema.clone()))
+            .collect::<HashMap<String, String>>();
+        files.insert(file.clone(), registered.schema.clone());
+
+        let mut resolver = ChainFileResolver::new();
+        resolver.add(RegistryFiles { files });
+        resolver.add(GoogleFileResolver::new());
+        let mut compiler = Compiler::with_file_resolver(resolver);
+        compiler.open_file(&file)?;
+
+        Ok(ParsedSchema {
+            pool: compiler.descriptor_pool(),
+            file,
+        })
+    }
+}
+
+impl MessageDecoder for RegistryProtobufDecoder {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Decoded> {
+        let (id, body) = split_wire_format(data)?;
+        let (indexes, body) = split_message_indexes(body)?;
+        let schema = self.schemas.get(id)?;
+
+        let unknown = || CodecError::UnknownMessage(format!("{:?} in schema {}", indexes, id));
+        let file = schema
+            .pool
+            .get_file_by_name(&schema.file)
+            .ok_or_else(unknown)?;
+        let mut message = file.messages().nth(indexes[0]).ok_or_else(unknown)?;
+        for index in &indexes[1..] {
+            let child = message.child_messages().nth(*index);
+            message = child.ok_or_else(unknown)?;
+        }
+        to_json(message, body)
+    }
+
+    fn prefetch(&self, data: &[u8]) -> bool {
+        match split_wire_format(data) {
+            Ok((id, _)) => self.schemas.prefetch(id),
+            // Data without schema ID is not decoded
+            Err(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::mock_registry::MockRegistry;
+    use crate::codec::protobuf::{load_descriptors, message_names, split_message_indexes};
+    use crate::codec::schema_registry::SchemaRegistry;
+    use crate::codec::{
+        Decoded, MessageDecoder, MessageEncoder, ProtobufDecoder, ProtobufEncoder,
+        RegistryProtobufDecoder,
+    };
+    use crate::kafka::SchemaRegistryConfig;
+    use serde_json::json;
+    use std::fs;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const ADDRESS: &str = r#"syntax = "proto3";
+        package test;
+        message Address { string city = 1; }"#;
+    const ORDER: &str = r#"syntax = "proto3";
+        package test;
+        import "address.proto";
+        message Order {
+            int64 id = 1;
+            Address address = 2;
+            message Item { string name = 1; int32 count = 2; }
+            repeated Item items = 3;
+            map<string, string> tags = 4;
+        }"#;
+
+    /// Order {id: 150, address: {city: "Riga"}}
+    const ORDER_DATA: &[u8] = &[
+        0x08, 0x96, 0x01, 0x12, 0x06, 0x0a, 0x04, b'R', b'i', b'g', b'a',
+    ];
+    /// Order.Item {name: "ab", count: 2}
+    const ITEM_DATA: &[u8] = &[0x0a, 0x02, b'a', b'b', 0x10, 0x02];
+
+    #[test]
+    fn test_protobuf_file() {
+        let dir = std::env::temp_dir().join(format!("kators_proto_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("address.proto"), ADDRESS).unwrap();
+        fs::write(dir.join("order.proto"), ORDER).unwrap();
+
+        let pool = load_descriptors(dir.join("order.proto")).unwrap();
+        assert_eq!(
+            vec!["test.Address", "test.Order", "test.Order.Item"],
+            message_names(&pool)
+        );
+
+        let decoder = ProtobufDecoder::from_pool(&pool, "test.Order").unwrap();
+        let expected = Decoded::Json(json!({
+            "id": 150, "address": {"city": "Riga"}, "items": [], "tags": {}
+        }));
+        assert_eq!(expected, decoder.decode(ORDER_DATA).unwrap());
+        // Confluent wire format with message index 0
+        let mut framed = vec![0, 0, 0, 0, 1, 0];
+        framed.extend(ORDER_DATA);
+        assert_eq!(expected, decoder.decode(&framed).unwrap());
+        assert!(decoder.decode(&[0x0a, 0x05, b'a']).is_err());
+
+        let encoder = ProtobufEncoder::new(pool.get_message_by_name("test.Order").unwrap());
+        let data = encoder
+            .encode(r#"{"id": 150, "address": {"city": "Riga"}}"#)
+            .unwrap();
+        assert_eq!(expected, decoder.decode(&data).unwrap());
+        assert!(encoder.encode(r#"{"unknown": 1}"#).is_err());
+
+        // Compiled descriptor set
+        fs::write(dir.join("order.pb"), pool.encode_to_vec()).unwrap();
+        let pool = load_descriptors(dir.join("order.pb")).unwrap();
+        let decoder = ProtobufDecoder::from_pool(&pool, "test.Order").unwrap();
+        assert_eq!(expected, decoder.decode(ORDER_DATA).unwrap());
+
+        assert!(ProtobufDecoder::from_pool(&pool, "test.Unknown").is_err());
+        assert!(load_descriptors(dir.join("unknown.proto")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_message_indexes() {
+        assert_eq!(
+            (vec![0], &[7u8][..]),
+            split_message_indexes(&[0, 7]).unwrap()
+        );
+        // Two indexes 1 and 0 in zigzag encoding
+        assert_eq!(
+            (vec![1, 0], &[7u8][..]),
+            split_message_indexes(&[4, 2, 0, 7]).unwrap()
+        );
+        assert!(split_message_indexes(&[2, 1]).is_err());
+        assert!(split_message_indexes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_protobuf_registry() {
+        let order_response = json!({
+            "schemaType": "PROTOBUF",
+            "schema": ORDER,
+            "references": [{"name": "address.proto", "subject": "address", "version": 1}]
+        })
+        .to_string();
+        let address_response = json!({"schemaType": "PROTOBUF", "schema": ADDRESS}).to_string();
+        let registry = MockRegistry::start(vec![
+            ("/schemas/ids/3", order_response.as_str()),
+            ("/subjects/address/versions/1", address_response.as_str()),
+        ]);
+        let mut decoder = RegistryProtobufDecoder::new(Arc::new(
+            SchemaRegistry::new(&SchemaRegistryConfig {
+                url: registry.url(),
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+        decoder.schemas.set_retry_after(Duration::ZERO);
+
+        // Order is the first message of the schema
+        let mut data = vec![0, 0, 0, 0, 3, 0];
+        data.extend(ORDER_DATA);
+        // Lookups failed by server errors are repeated
+        registry.fail_next(1);
+        assert!(decoder.decode(&data).is_err());
+        assert_eq!(
+            Decoded::Json(json!({
+                "id": 150, "address": {"city": "Riga"}, "items": [], "tags": {}
+            })),
+            decoder.decode(&data).unwrap()
+        );
+
+        // Item is the first nested message of Order
+        let mut data = vec![0, 0, 0, 0, 3, 4, 0, 0];
+        data.extend(ITEM_DATA);
+        assert_eq!(
+            Decoded::Json(json!({"name": "ab", "count": 2})),
+            decoder.decode(&data).unwrap()
+        );
+        assert_eq!(3, registry.requests());
+
+        // There is no second message
+        let mut data = vec![0, 0, 0, 0, 3, 2, 2];
+        data.extend(ORDER_DATA);
+        assert!(decoder.decode(&data).is_err());
+
+        // Lookups of schemas not registered are not repeated
+        let mut data = vec![0, 0, 0, 0, 4, 0];
+        data.extend(ORDER_DATA);
+        assert!(decoder.decode(&data).is_err());
+        assert!(decoder.decode(&data).is_err());
+        assert_eq!(4, registry.requests());
+    }
+}
@@ -0,0 +1,464 @@
+use crate::codec::CodecError;
+use crate::kafka::SchemaRegistryConfig;
+use anyhow::{anyhow, Result};
+use log::error;
+use reqwest::blocking::Client;
+use reqwest::{Certificate, Identity};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::str::from_utf8;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Magic byte of the Confluent wire format
+const MAGIC_BYTE: u8 = 0;
+/// Schema lookups failed by timeouts, connection and server errors are repeated after it
+const RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// Reference to a schema registered under another subject
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SchemaReference {
+    pub name: String,
+    pub subject: String,
+    pub version: i32,
+}
+
+/// Schema as it is returned by the registry
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredSchema {
+    /// Global schema ID, returned only for subject versions
+    #[serde(default)]
+    pub id: Option<u32>,
+    /// AVRO, PROTOBUF or JSON. Registry omits the type for Avro schemas
+    #[serde(default = "default_schema_type")]
+    pub schema_type: String,
+    pub schema: String,
+    #[serde(default)]
+    pub references: Vec<SchemaReference>,
+}
+
+fn default_schema_type() -> String {
+    "AVRO".to_string()
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CompatibilityConfig {
+    compatibility_level: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct RegistryError {
+    error_code: i32,
+    message: String,
+}
+
+/// Client of the Confluent Schema Registry REST API. Schemas are immutable,
+/// so all of them are cached after the first request
+#[derive(Debug)]
+pub struct SchemaRegistry {
+    url: String,
+    client: Client,
+    username: String,
+    password: Option<String>,
+    by_id: Mutex<HashMap<u32, Arc<RegisteredSchema>>>,
+    by_version: Mutex<HashMap<(String, i32), Arc<RegisteredSchema>>>,
+}
+
+impl SchemaRegistry {
+    pub fn new(config: &SchemaRegistryConfig) -> Result<Self> {
+        let mut builder = Client::builder().timeout(Duration::from_secs(10));
+        if !config.ssl_ca_location.is_empty() {
+            let pem = fs::read(&config.ssl_ca_location)?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+        if !config.ssl_keystore_location.is_empty() {
+            let der = fs::read(&config.ssl_keystore_location)?;
+            let password = match &config.ssl_keystore_password {
+                Some(password) => String::from_utf8(password.decrypt()?)?,
+                None => "".to_string(),
+            };
+            builder = builder.identity(Identity::from_pkcs12_der(&der, &password)?);
+        }
+        let password = match &config.password {
+            Some(password) => Some(String::from_utf8(password.decrypt()?)?),
+            None => None,
+        };
+
+        Ok(Self {
+            url: config.url.trim_end_matches('/').to_string(),
+            client: builder.build()?,
+            username: config.username.clone(),
+            password,
+            by_id: Mutex::new(HashMap::new()),
+            by_version: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Sends GET request to the registry and parses JSON response
+    fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let mut request = self
+            .client
+            .get(format!("{}{}", self.url, path))
+            .header("Accept", "application/vnd.schemaregistry.v1+json");
+        if !self.username.is_empty() {
+            request = request.basic_auth(&self.username, self.password.as_ref());
+        }
+
+        let response = request.send()?;
+        let status = response.status();
+        let body = response.bytes()?;
+        if status.is_success() {
+            Ok(serde_json::from_slice(&body)?)
+        } else {
+            let message = match serde_json::from_slice::<RegistryError>(&body) {
+                Ok(err) => format!("{} (code {})", err.message, err.error_code),
+                Err(_) => from_utf8(&body).unwrap_or_default().to_string(),
+            };
+            Err(CodecError::Registry(status.as_u16(), message).into())
+        }
+    }
+
+    /// Returns schema with the given global ID
+    pub fn schema_by_id(&self, id: u32) -> Result<Arc<RegisteredSchema>> {
+        if let Some(schema) = self.by_id.lock().unwrap().get(&id) {
+            return Ok(schema.clone());
+        }
+
+        let schema = Arc::new(self.get::<RegisteredSchema>(&format!("/schemas/ids/{}", id))?);
+        self.by_id.lock().unwrap().insert(id, schema.clone());
+        Ok(schema)
+    }
+
+    /// Returns schema registered under the subject with the given version
+    pub fn schema_by_version(&self, subject: &str, version: i32) -> Result<Arc<RegisteredSchema>> {
+        let key = (subject.to_string(), version);
+        if let Some(schema) = self.by_version.lock().unwrap().get(&key) {
+            return Ok(schema.clone());
+        }
+
+        let schema = Arc::new(self.get::<RegisteredSchema>(&format!(
+            "/subjects/{}/versions/{}",
+            encode_segment(subject),
+            version
+        ))?);
+        self.by_version.lock().unwrap().insert(key, schema.clone());
+        Ok(schema)
+    }
+
+    /// Names of all registered subjects
+    pub fn subjects(&self) -> Result<Vec<String>> {
+        let mut subjects = self.get::<Vec<String>>("/subjects")?;
+        subjects.sort();
+        Ok(subjects)
+    }
+
+    /// Versions registered under the subject in ascending order
+    pub fn versions(&self, subject: &str) -> Result<Vec<i32>> {
+        let mut versions =
+            self.get::<Vec<i32>>(&format!("/subjects/{}/versions", encode_segment(subject)))?;
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    /// Compatibility level of the subject. Global level is returned when the subject
+    /// has no own configuration
+    pub fn compatibility(&self, subject: &str) -> Result<String> {
+        let config = match self.get::<CompatibilityConfig>(&format!(
+            "/config/{}?defaultToGlobal=true",
+            encode_segment(subject)
+        )) {
+            Ok(config) => config,
+            Err(_) => self.get::<CompatibilityConfig>("/config")?,
+        };
+        Ok(config.compatibility_level)
+    }
+
+    /// Returns all schemas referenced by the schema directly or indirectly.
+    /// Dependencies go before the schemas which use them
+    pub fn references(
+        &self,
+        schema: &RegisteredSchema,
+    ) -> Result<Vec<(String, Arc<RegisteredSchema>)>> {
+        let mut result = vec![];
+        self.collect_references(schema, &mut result)?;
+        Ok(result)
+    }
+
+    fn collect_references(
+        &self,
+        schema: &RegisteredSchema,
+        result: &mut Vec<(String, Arc<RegisteredSchema>)>,
+    ) -> Result<()> {
+        for reference in &schema.references {
+            if result.iter().any(|(name, _)| name == &reference.name) {
+                continue;
+            }
+            let referenced = self.schema_by_version(&reference.subject, reference.version)?;
+            self.collect_references(&referenced, result)?;
+            result.push((reference.name.clone(), referenced));
+        }
+        Ok(())
+    }
+}
+
+/// Schema parsed by a decoder or state of its lookup
+#[derive(Debug)]
+enum CachedSchema<T> {
+    /// Lookup is in progress, [SchemaCache::get] waits for it
+    Loading,
+    Loaded(Arc<T>),
+    /// Error message and time when the lookup may be repeated. Lookups failed because
+    /// the schema is not registered or can't be parsed are not repeated
+    Failed(String, Option<Instant>),
+}
+
+/// Cached schemas and condition notified when a lookup is finished
+type Schemas<T> = (Mutex<HashMap<u32, CachedSchema<T>>>, Condvar);
+
+/// Schemas loaded from the registry and parsed by a decoder, by ID. Schemas may be
+/// prefetched in the background, so the GUI is not blocked by registry requests
+#[derive(Debug)]
+pub struct SchemaCache<T> {
+    registry: Arc<SchemaRegistry>,
+    parse: fn(&SchemaRegistry, u32) -> Result<T>,
+    schemas: Arc<Schemas<T>>,
+    retry_after: Duration,
+}
+
+impl<T: Send + Sync + 'static> SchemaCache<T> {
+    /// Creates cache loading schemas with `parse`
+    pub fn new(
+        registry: Arc<SchemaRegistry>,
+        parse: fn(&SchemaRegistry, u32) -> Result<T>,
+    ) -> Self {
+        Self {
+            registry,
+            parse,
+            schemas: Arc::new((Mutex::new(HashMap::new()), Condvar::new())),
+            retry_after: RETRY_AFTER,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_retry_after(&mut self, retry_after: Duration) {
+        self.retry_after = retry_after;
+    }
+
+    /// Returns schema with the given ID, waits for the lookup started by prefetch. Failed
+    /// lookups are cached too, so the registry is not requested for every message while
+    /// it is unavailable
+    pub fn get(&self, id: u32) -> Result<Arc<T>> {
+        let (lock, finished) = &*self.schemas;
+        let mut schemas = lock.lock().unwrap();
+        while let Some(CachedSchema::Loading) = schemas.get(&id) {
+            schemas = finished.wait(schemas).unwrap();
+        }
+        if let Some(cached) = Self::cached(&schemas, id) {
+            return cached;
+        }
+        schemas.insert(id, CachedSchema::Loading);
+        drop(schemas);
+
+        Self::load(
+            &self.registry,
+            self.parse,
+            &self.schemas,
+            self.retry_after,
+            id,
+        )
+    }
+
+    /// Starts lookup of the schema in the background unless it is cached. Returns true
+    /// when the schema is loaded or its lookup failed
+    pub fn prefetch(&self, id: u32) -> bool {
+        let mut schemas = self.schemas.0.lock().unwrap();
+        if let Some(CachedSchema::Loading) = schemas.get(&id) {
+            return false;
+        }
+        if Self::cached(&schemas, id).is_some() {
+            return true;
+        }
+        schemas.insert(id, CachedSchema::Loading);
+        drop(schemas);
+
+        let registry = self.registry.clone();
+        let parse = self.parse;
+        let schemas = self.schemas.clone();
+        let retry_after = self.retry_after;
+        thread::spawn(move || {
+            if let Err(err) = Self::load(&registry, parse, &schemas, retry_after, id) {
+                error!("Error loading schema {}: {}", id, err);
+            }
+        });
+        false
+    }
+
+    /// Returns loaded schema or error of the failed lookup, None if the schema needs to
+    /// be loaded
+    fn cached(schemas: &HashMap<u32, CachedSchema<T>>, id: u32) -> Option<Result<Arc<T>>> {
+        match schemas.get(&id)? {
+            CachedSchema::Loaded(schema) => Some(Ok(schema.clone())),
+            CachedSchema::Failed(err, retry_at)
+                if retry_at.map(|at| Instant::now() < at).unwrap_or(true) =>
+            {
+                Some(Err(anyhow!(err.clone())))
+            }
+            _ => None,
+        }
+    }
+
+    /// Loads and parses the schema, stores the result and wakes up threads waiting for it
+    fn load(
+        registry: &SchemaRegistry,
+        parse: fn(&SchemaRegistry, u32) -> Result<T>,
+        schemas: &Schemas<T>,
+        retry_after: Duration,
+        id: u32,
+    ) -> Result<Arc<T>> {
+        let (cached, result) = match parse(registry, id) {
+            Ok(schema) => {
+                let schema = Arc::new(schema);
+                (CachedSchema::Loaded(schema.clone()), Ok(schema))
+            }
+            Err(err) => {
+                let retry_at = (!is_definite(&err)).then(|| Instant::now() + retry_after);
+                (CachedSchema::Failed(err.to_string(), retry_at), Err(err))
+            }
+        };
+        let (lock, finished) = schemas;
+        lock.lock().unwrap().insert(id, cached);
+        finished.notify_all();
+        result
+    }
+}
+
+/// Returns whether the schema lookup fails the same way when it is repeated: the schema
+/// is not registered or it is invalid. Connection errors, timeouts, server errors and
+/// malformed responses may pass
+fn is_definite(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<CodecError>() {
+        Some(CodecError::Registry(status, _)) => matches!(status, 404 | 422),
+        Some(_) => true,
+        None => {
+            err.downcast_ref::<reqwest::Error>().is_none()
+                && err.downcast_ref::<serde_json::Error>().is_none()
+        }
+    }
+}
+
+/// Percent-encodes subject name to use it as URL path segment
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Splits data in the Confluent wire format into schema ID and body
+pub fn split_wire_format(data: &[u8]) -> Result<(u32, &[u8])> {
+    match data {
+        [MAGIC_BYTE, b0, b1, b2, b3, body @ ..] => {
+            Ok((u32::from_be_bytes([*b0, *b1, *b2, *b3]), body))
+        }
+        _ => Err(CodecError::InvalidData(
+            "wire format".to_string(),
+            "no magic byte and schema ID".to_string(),
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::mock_registry::MockRegistry;
+    use crate::codec::schema_registry::{split_wire_format, SchemaRegistry};
+    use crate::kafka::{Password, SchemaRegistryConfig};
+
+    #[test]
+    fn test_schema_cache_and_auth() {
+        let registry = MockRegistry::start(vec![
+            (
+                "/schemas/ids/7",
+                r#"{"schema": "\"string\"", "references": [{"name": "a.avsc", "subject": "a", "version": 2}]}"#,
+            ),
+            (
+                "/subjects/a/versions/2",
+                r#"{"subject": "a", "version": 2, "id": 3, "schema": "\"int\""}"#,
+            ),
+        ]);
+        let client = SchemaRegistry::new(&SchemaRegistryConfig {
+            url: registry.url(),
+            username: "user".to_string(),
+            password: Some(Password::new("secret").unwrap()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let schema = client.schema_by_id(7).unwrap();
+        assert_eq!("AVRO", schema.schema_type);
+        assert_eq!("\"string\"", schema.schema);
+        assert_eq!(schema, client.schema_by_id(7).unwrap());
+        assert_eq!(1, registry.requests());
+
+        let references = client.references(&schema).unwrap();
+        assert_eq!(1, references.len());
+        assert_eq!("a.avsc", references[0].0);
+        assert_eq!("\"int\"", references[0].1.schema);
+        assert_eq!(2, registry.requests());
+        // "user:secret" in base64
+        assert_eq!(
+            Some("Basic dXNlcjpzZWNyZXQ=".to_string()),
+            registry.last_authorization()
+        );
+
+        assert!(client.schema_by_id(8).is_err());
+    }
+
+    #[test]
+    fn test_subjects() {
+        let registry = MockRegistry::start(vec![
+            ("/subjects", r#"["orders-value", "a/b"]"#),
+            ("/subjects/a%2Fb/versions", "[3, 1, 2]"),
+            (
+                "/subjects/a%2Fb/versions/2",
+                r#"{"subject": "a/b", "version": 2, "id": 11, "schema": "\"int\""}"#,
+            ),
+            (
+                "/config/a%2Fb?defaultToGlobal=true",
+                r#"{"compatibilityLevel": "FULL"}"#,
+            ),
+            ("/config", r#"{"compatibilityLevel": "BACKWARD"}"#),
+        ]);
+        let client = SchemaRegistry::new(&SchemaRegistryConfig {
+            url: registry.url(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(vec!["a/b", "orders-value"], client.subjects().unwrap());
+        assert_eq!(vec![1, 2, 3], client.versions("a/b").unwrap());
+        assert_eq!(Some(11), client.schema_by_version("a/b", 2).unwrap().id);
+        assert_eq!("FULL", client.compatibility("a/b").unwrap());
+        assert_eq!("BACKWARD", client.compatibility("orders-value").unwrap());
+        assert!(client.versions("orders-value").is_err());
+    }
+
+    #[test]
+    fn test_wire_format() {
+        let (id, body) = split_wire_format(&[0, 0, 0, 1, 2, 10, 11]).unwrap();
+        assert_eq!(258, id);
+        assert_eq!(&[10, 11], body);
+        assert!(split_wire_format(&[1, 0, 0, 1, 2]).is_err());
+        assert!(split_wire_format(&[0, 0, 1]).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Timelike};
+use eframe::egui;
+use eframe::egui::{Color32, Ui};
+
+/// Date and time editor. Values are interpreted in the local time zone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTimeEdit {
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl DateTimeEdit {
+    /// Creates editor initialized by current local time
+    pub fn now() -> Self {
+        let now = Local::now();
+        Self {
+            year: now.year(),
+            month: now.month(),
+            day: now.day(),
+            hour: now.hour(),
+            minute: now.minute(),
+            second: now.second(),
+        }
+    }
+
+    /// Returns milliseconds since Unix epoch or `None` if the entered date does not exist
+    pub fn timestamp_millis(&self) -> Option<i64> {
+        let naive = NaiveDate::from_ymd_opt(self.year, self.month, self.day)?.and_hms_opt(
+            self.hour,
+            self.minute,
+            self.second,
+        )?;
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.timestamp_millis())
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.add(egui::DragValue::new(&mut self.year).clamp_range(1970..=9999));
+        ui.label("-");
+        ui.add(egui::DragValue::new(&mut self.month).clamp_range(1..=12));
+        ui.label("-");
+        ui.add(egui::DragValue::new(&mut self.day).clamp_range(1..=31));
+        ui.add_space(4.0);
+        ui.add(egui::DragValue::new(&mut self.hour).clamp_range(0..=23));
+        ui.label(":");
+        ui.add(egui::DragValue::new(&mut self.minute).clamp_range(0..=59));
+        ui.label(":");
+        ui.add(egui::DragValue::new(&mut self.second).clamp_range(0..=59));
+
+        if ui.button("now").on_hover_text("Set current time").clicked() {
+            *self = Self::now();
+        }
+
+        if self.timestamp_millis().is_none() {
+            ui.colored_label(Color32::RED, "invalid date");
+        }
+    }
+}
+
+/// Formats milliseconds since Unix epoch as local date and time
+pub fn format_timestamp(millis: i64) -> String {
+    match Local.timestamp_millis_opt(millis).single() {
+        Some(dt) => dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+        None => millis.to_string(),
+    }
+}
@@ -0,0 +1,203 @@
+use crate::d_gui::group_reset::ResetOffsets;
+use crate::d_gui::model::KafkaTreeGroup;
+use crate::kafka::{ConsumerOps, KafkaServer, PartitionLag};
+use anyhow::Result as AnyResult;
+use eframe::egui;
+use eframe::egui::Ui;
+use log::error;
+use sled::Tree as DbTree;
+use std::rc::Rc;
+
+/// Panel with consumer group information: members and committed offsets
+#[derive(Debug)]
+pub struct GroupInfo {
+    db: Rc<DbTree>,
+    group: KafkaTreeGroup,
+    consumer: Option<ConsumerOps>,
+    offsets: Vec<PartitionLag>,
+    reset: ResetOffsets,
+    reset_open: bool,
+}
+
+impl GroupInfo {
+    pub fn new(db: Rc<DbTree>) -> Self {
+        Self {
+            db,
+            group: KafkaTreeGroup {
+                server_name: "".to_string(),
+                name: "".to_string(),
+                state: "".to_string(),
+                protocol_type: "".to_string(),
+                protocol: "".to_string(),
+                members: vec![],
+            },
+            consumer: None,
+            offsets: vec![],
+            reset: ResetOffsets::new(),
+            reset_open: false,
+        }
+    }
+
+    pub fn set_group(&mut self, group: &KafkaTreeGroup) -> &mut Self {
+        if self.group.server_name != group.server_name || self.group.name != group.name {
+            self.group = group.clone();
+            self.offsets.clear();
+            self.reset_open = false;
+            // Consumer must use the group to read its committed offsets
+            self.consumer = match KafkaServer::from_db(&self.db, &group.server_name)
+                .and_then(|server| server.open_with_group(&group.name))
+                .and_then(|ops| ops.create_consumer())
+            {
+                Ok(consumer) => Some(consumer),
+                Err(err) => {
+                    error!("Error connecting to server {}: {}", &group.server_name, err);
+                    None
+                }
+            };
+            self.refresh();
+        }
+
+        self
+    }
+
+    /// Reloads group description and committed offsets
+    fn refresh(&mut self) {
+        if let Err(err) = self.read_group() {
+            error!("Error reading group {}: {}", &self.group.name, err);
+        }
+    }
+
+    fn read_group(&mut self) -> AnyResult<()> {
+        if let Some(consumer) = &self.consumer {
+            let list = consumer.read_groups(Some(&self.group.name))?;
+            if let Some(group) = list.groups().first() {
+                self.group = KafkaTreeGroup::from_group(&self.group.server_name, group);
+            }
+            self.offsets = consumer.read_group_offsets()?;
+        }
+        Ok(())
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("refresh").clicked() {
+                self.refresh();
+            }
+
+            if ui
+                .add_enabled(self.consumer.is_some(), egui::Button::new("Reset offsets"))
+                .clicked()
+            {
+                if let Some(consumer) = &self.consumer {
+                    match self.reset.load(consumer, &self.offsets) {
+                        Ok(_) => self.reset_open = true,
+                        Err(err) => error!("Error reading topics: {}", err),
+                    }
+                }
+            }
+        });
+        ui.separator();
+
+        let mut committed = false;
+        if let Some(consumer) = &self.consumer {
+            egui::Window::new(format!("Reset offsets: {}", &self.group.name))
+                .id(egui::Id::new("reset_offsets"))
+                .open(&mut self.reset_open)
+                .default_width(600.0)
+                .show(ui.ctx(), |ui| {
+                    committed = self.reset.ui(
+                        ui,
+                        &self.group.name,
+                        consumer,
+                        !self.group.members.is_empty(),
+                    );
+                });
+        }
+        if committed {
+            self.refresh();
+        }
+
+        egui::Grid::new("group_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Name");
+                ui.label(&self.group.name);
+                ui.end_row();
+
+                ui.label("State");
+                ui.label(&self.group.state);
+                ui.end_row();
+
+                ui.label("Protocol type");
+                ui.label(&self.group.protocol_type);
+                ui.end_row();
+
+                ui.label("Protocol");
+                ui.label(&self.group.protocol);
+                ui.end_row();
+
+                ui.label("Total lag");
+                ui.label(
+                    self.offsets
+                        .iter()
+                        .map(|o| o.lag())
+                        .sum::<i64>()
+                        .to_string(),
+                );
+                ui.end_row();
+            });
+
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                egui::CollapsingHeader::new(format!("Members ({})", self.group.members.len()))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new("group_members_grid")
+                            .num_columns(3)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Id");
+                                ui.strong("Client id");
+                                ui.strong("Host");
+                                ui.end_row();
+
+                                for m in &self.group.members {
+                                    ui.label(&m.id);
+                                    ui.label(&m.client_id);
+                                    ui.label(&m.host);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                egui::CollapsingHeader::new(format!("Offsets ({})", self.offsets.len()))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new("group_offsets_grid")
+                            .num_columns(5)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Topic");
+                                ui.strong("Partition");
+                                ui.strong("Committed");
+                                ui.strong("End");
+                                ui.strong("Lag");
+                                ui.end_row();
+
+                                for o in &self.offsets {
+                                    ui.label(&o.topic);
+                                    ui.label(o.partition.to_string());
+                                    ui.label(o.committed.to_string());
+                                    ui.label(o.high.to_string());
+                                    ui.label(o.lag().to_string());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+    }
+}
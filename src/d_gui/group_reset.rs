@@ -0,0 +1,273 @@
+use crate::d_gui::date_time::DateTimeEdit;
+use crate::kafka::{ConsumerOps, OffsetChange, OffsetReset, PartitionLag};
+use anyhow::Result as AnyResult;
+use eframe::egui;
+use eframe::egui::{Color32, Ui};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum ResetKind {
+    Earliest,
+    Latest,
+    Timestamp,
+    Offset,
+    Shift,
+}
+
+impl Display for ResetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResetKind::Earliest => f.write_str("Earliest"),
+            ResetKind::Latest => f.write_str("Latest"),
+            ResetKind::Timestamp => f.write_str("Timestamp"),
+            ResetKind::Offset => f.write_str("Offset"),
+            ResetKind::Shift => f.write_str("Shift by"),
+        }
+    }
+}
+
+/// Topic with partitions selected for the offsets reset
+#[derive(Debug)]
+struct TopicSelection {
+    name: String,
+    partitions: Vec<(i32, bool)>,
+}
+
+/// Consumer group offsets reset workflow. New offsets are computed and shown first,
+/// only the previewed offsets are committed
+#[derive(Debug)]
+pub struct ResetOffsets {
+    topics: Vec<TopicSelection>,
+    topic_filter: String,
+    kind: ResetKind,
+    offset: i64,
+    shift: i64,
+    timestamp: DateTimeEdit,
+    preview: Vec<OffsetChange>,
+    status: Result<String, String>,
+}
+
+impl ResetOffsets {
+    pub fn new() -> Self {
+        Self {
+            topics: vec![],
+            topic_filter: "".to_string(),
+            kind: ResetKind::Earliest,
+            offset: 0,
+            shift: 0,
+            timestamp: DateTimeEdit::now(),
+            preview: vec![],
+            status: Ok("".to_string()),
+        }
+    }
+
+    /// Loads topics list. Partitions with offsets committed by the group are selected
+    pub fn load(&mut self, consumer: &ConsumerOps, committed: &[PartitionLag]) -> AnyResult<()> {
+        let md = consumer.read_metadata()?;
+        let mut topics = md
+            .topics()
+            .iter()
+            .filter(|t| !t.name().starts_with("__"))
+            .map(|t| TopicSelection {
+                name: t.name().to_string(),
+                partitions: t
+                    .partitions()
+                    .iter()
+                    .map(|p| {
+                        let selected = committed
+                            .iter()
+                            .any(|c| c.topic == t.name() && c.partition == p.id());
+                        (p.id(), selected)
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<TopicSelection>>();
+        topics.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.topics = topics;
+        self.preview.clear();
+        self.status = Ok("".to_string());
+        Ok(())
+    }
+
+    fn selected(&self) -> Vec<(String, i32)> {
+        self.topics
+            .iter()
+            .flat_map(|t| {
+                t.partitions
+                    .iter()
+                    .filter(|(_, selected)| *selected)
+                    .map(move |(p, _)| (t.name.clone(), *p))
+            })
+            .collect()
+    }
+
+    fn reset(&self) -> Option<OffsetReset> {
+        match self.kind {
+            ResetKind::Earliest => Some(OffsetReset::Earliest),
+            ResetKind::Latest => Some(OffsetReset::Latest),
+            ResetKind::Timestamp => self
+                .timestamp
+                .timestamp_millis()
+                .map(OffsetReset::Timestamp),
+            ResetKind::Offset => Some(OffsetReset::Offset(self.offset)),
+            ResetKind::Shift => Some(OffsetReset::Shift(self.shift)),
+        }
+    }
+
+    /// Draws reset window content. Returns `true` if offsets were committed
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        group: &str,
+        consumer: &ConsumerOps,
+        has_members: bool,
+    ) -> bool {
+        let mut committed = false;
+        // Preview is valid only for the inputs it was computed with
+        let inputs = (self.reset(), self.selected());
+
+        ui.horizontal(|ui| {
+            ui.label("Reset to");
+            egui::ComboBox::from_id_source("reset_kind")
+                .selected_text(self.kind.to_string())
+                .show_ui(ui, |ui| {
+                    [
+                        ResetKind::Earliest,
+                        ResetKind::Latest,
+                        ResetKind::Timestamp,
+                        ResetKind::Offset,
+                        ResetKind::Shift,
+                    ]
+                    .into_iter()
+                    .for_each(|k| {
+                        ui.selectable_value(&mut self.kind, k, k.to_string());
+                    });
+                });
+
+            match self.kind {
+                ResetKind::Timestamp => self.timestamp.ui(ui),
+                ResetKind::Offset => {
+                    ui.add(egui::DragValue::new(&mut self.offset).clamp_range(0..=i64::MAX));
+                }
+                ResetKind::Shift => {
+                    ui.add(egui::DragValue::new(&mut self.shift));
+                }
+                _ => {}
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Topics");
+            ui.text_edit_singleline(&mut self.topic_filter);
+        });
+        egui::ScrollArea::vertical()
+            .id_source("reset_topics")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for topic in self
+                    .topics
+                    .iter_mut()
+                    .filter(|t| t.name.contains(&self.topic_filter))
+                {
+                    ui.horizontal(|ui| {
+                        let mut all = topic.partitions.iter().all(|(_, s)| *s);
+                        if ui.checkbox(&mut all, &topic.name).changed() {
+                            topic.partitions.iter_mut().for_each(|(_, s)| *s = all);
+                        }
+                        for (p, selected) in topic.partitions.iter_mut() {
+                            ui.checkbox(selected, p.to_string());
+                        }
+                    });
+                }
+            });
+
+        if inputs != (self.reset(), self.selected()) {
+            self.preview.clear();
+            self.status = Ok("".to_string());
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Preview").clicked() {
+                self.status = match self.reset() {
+                    Some(reset) => consumer
+                        .plan_group_reset(&self.selected(), &reset)
+                        .map(|preview| {
+                            self.preview = preview;
+                            format!("{} partitions to change", self.preview.len())
+                        })
+                        .map_err(|e| e.to_string()),
+                    None => Err("invalid date".to_string()),
+                };
+            }
+
+            if ui
+                .add_enabled(
+                    !has_members && !self.preview.is_empty(),
+                    egui::Button::new("Apply"),
+                )
+                .on_disabled_hover_text("Preview changes first. The group must have no members")
+                .clicked()
+            {
+                self.status = consumer
+                    .commit_group_offsets(group, &self.preview)
+                    .map(|_| {
+                        committed = true;
+                        format!("{} offsets committed", self.preview.len())
+                    })
+                    .map_err(|e| e.to_string());
+                self.preview.clear();
+            }
+
+            match &self.status {
+                Ok(text) => ui.label(text),
+                Err(text) => ui.colored_label(Color32::RED, text),
+            };
+        });
+
+        if has_members {
+            ui.colored_label(
+                Color32::RED,
+                "Group has active members, stop consumers before reset",
+            );
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("reset_preview")
+            .show(ui, |ui| {
+                egui::Grid::new("reset_preview_grid")
+                    .num_columns(5)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Topic");
+                        ui.strong("Partition");
+                        ui.strong("Current");
+                        ui.strong("New");
+                        ui.strong("Difference");
+                        ui.end_row();
+
+                        for change in &self.preview {
+                            ui.label(&change.topic);
+                            ui.label(change.partition.to_string());
+                            match change.current {
+                                Some(current) => {
+                                    ui.label(current.to_string());
+                                    ui.label(change.new.to_string());
+                                    ui.label(format!("{:+}", change.new - current));
+                                }
+                                None => {
+                                    ui.label("-");
+                                    ui.label(change.new.to_string());
+                                    ui.label("");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        committed
+    }
+}
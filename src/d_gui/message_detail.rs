@@ -0,0 +1,389 @@
+use crate::codec::{hex_preview, Decoded, MessageDecoder};
+use crate::d_gui::date_time::format_timestamp;
+use anyhow::Result;
+use eframe::egui;
+use eframe::egui::text::{LayoutJob, TextFormat};
+use eframe::egui::{Color32, FontId, RichText, TextStyle, Ui};
+use rdkafka::message::{Headers, OwnedMessage, Timestamp};
+use rdkafka::Message;
+use serde_json::Value;
+use std::fmt::{Display, Formatter, Write};
+use std::str::from_utf8;
+
+/// Number of bytes in one line of hex dump
+const DUMP_LINE: usize = 16;
+
+/// Maximum number of bytes shown in hex dump, the rest is truncated
+const MAX_DUMP_BYTES: usize = 64 * 1024;
+
+/// Levels of JSON tree opened by default
+const TREE_OPEN_DEPTH: usize = 2;
+
+const KEY_COLOR: Color32 = Color32::from_rgb(156, 220, 254);
+const STRING_COLOR: Color32 = Color32::from_rgb(206, 145, 120);
+const NUMBER_COLOR: Color32 = Color32::from_rgb(181, 206, 168);
+const LITERAL_COLOR: Color32 = Color32::from_rgb(86, 156, 214);
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum JsonView {
+    Pretty,
+    Tree,
+}
+
+impl Display for JsonView {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonView::Pretty => f.write_str("Pretty"),
+            JsonView::Tree => f.write_str("Tree"),
+        }
+    }
+}
+
+/// Full information about the selected message: metadata, headers, key and payload.
+/// Key and payload are shown by the topic decoders, hex dump is used for binary data
+#[derive(Debug)]
+pub struct MessageDetail {
+    json_view: JsonView,
+}
+
+impl MessageDetail {
+    pub fn new() -> Self {
+        Self {
+            json_view: JsonView::Pretty,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        msg: &OwnedMessage,
+        key_decoder: &dyn MessageDecoder,
+        data_decoder: &dyn MessageDecoder,
+    ) {
+        egui::Grid::new("message_detail_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Topic");
+                ui.label(msg.topic());
+                ui.end_row();
+
+                ui.label("Partition");
+                ui.label(msg.partition().to_string());
+                ui.end_row();
+
+                ui.label("Offset");
+                ui.label(msg.offset().to_string());
+                ui.end_row();
+
+                ui.label("Timestamp");
+                ui.label(match msg.timestamp() {
+                    Timestamp::NotAvailable => "not available".to_string(),
+                    Timestamp::CreateTime(ts) => {
+                        format!("{} (create time)", format_timestamp(ts))
+                    }
+                    Timestamp::LogAppendTime(ts) => {
+                        format!("{} (log append time)", format_timestamp(ts))
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Key size");
+                ui.label(size_text(msg.key()));
+                ui.end_row();
+
+                ui.label("Payload size");
+                ui.label(size_text(msg.payload()));
+                ui.end_row();
+            });
+
+        let headers = msg.headers();
+        let count = headers.map(|h| h.count()).unwrap_or(0);
+        egui::CollapsingHeader::new(format!("Headers ({})", count))
+            .default_open(count > 0)
+            .show(ui, |ui| {
+                egui::Grid::new("message_headers_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Name");
+                        ui.strong("Value");
+                        ui.strong("Size");
+                        ui.end_row();
+
+                        for (name, value) in headers
+                            .iter()
+                            .flat_map(|h| (0..h.count()).filter_map(|i| h.get(i)))
+                        {
+                            ui.label(name);
+                            match from_utf8(value) {
+                                Ok(text) => ui.label(text),
+                                Err(_) => ui.monospace(hex_preview(value, 64)),
+                            };
+                            ui.label(value.len().to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        egui::CollapsingHeader::new("Key")
+            .default_open(true)
+            .show(ui, |ui| {
+                self.show_data(ui, "message_key", msg.key(), key_decoder)
+            });
+        egui::CollapsingHeader::new("Payload")
+            .default_open(true)
+            .show(ui, |ui| {
+                self.show_data(ui, "message_payload", msg.payload(), data_decoder)
+            });
+    }
+
+    fn show_data(
+        &mut self,
+        ui: &mut Ui,
+        id: &str,
+        data: Option<&[u8]>,
+        decoder: &dyn MessageDecoder,
+    ) {
+        let data = match data {
+            Some(data) => data,
+            None => {
+                ui.label(RichText::new("null").italics());
+                return;
+            }
+        };
+
+        if !decoder.prefetch(data) {
+            ui.label(RichText::new("loading schema...").italics());
+            ui.ctx().request_repaint();
+            return;
+        }
+        let mut text = match decoder.decode(data) {
+            Ok(Decoded::Text(text)) => text,
+            Ok(Decoded::Binary(data)) => hex_dump(&data),
+            Ok(Decoded::Json(value)) => {
+                self.show_json(ui, id, &value);
+                return;
+            }
+            Err(err) => {
+                ui.colored_label(Color32::RED, err.to_string());
+                hex_dump(data)
+            }
+        };
+        ui.add(
+            egui::TextEdit::multiline(&mut text)
+                .id_source(id)
+                .code_editor()
+                .desired_rows(4)
+                .lock_focus(true)
+                .desired_width(f32::INFINITY),
+        );
+    }
+
+    fn show_json(&mut self, ui: &mut Ui, id: &str, value: &Value) {
+        ui.horizontal(|ui| {
+            [JsonView::Pretty, JsonView::Tree]
+                .into_iter()
+                .for_each(|v| {
+                    ui.selectable_value(&mut self.json_view, v, v.to_string());
+                });
+        });
+
+        match self.json_view {
+            JsonView::Pretty => {
+                let mut text = serde_json::to_string_pretty(value).unwrap_or_default();
+                let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+                    let mut job = highlight_json(text, TextStyle::Monospace.resolve(ui.style()));
+                    job.wrap_width = wrap_width;
+                    ui.fonts().layout_job(job)
+                };
+                ui.add(
+                    egui::TextEdit::multiline(&mut text)
+                        .id_source(id)
+                        .code_editor()
+                        .desired_rows(4)
+                        .lock_focus(true)
+                        .desired_width(f32::INFINITY)
+                        .layouter(&mut layouter),
+                );
+            }
+            JsonView::Tree => show_json_node(ui, id, "$", value, 0),
+        }
+    }
+}
+
+/// Draws JSON value as tree. Objects and arrays are collapsible
+fn show_json_node(ui: &mut Ui, path: &str, name: &str, value: &Value, depth: usize) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("[{}]", i), v))
+            .collect(),
+        _ => {
+            ui.horizontal(|ui| {
+                ui.colored_label(KEY_COLOR, name);
+                ui.label(":");
+                let (color, text) = match value {
+                    Value::String(s) => (STRING_COLOR, format!("{:?}", s)),
+                    Value::Number(n) => (NUMBER_COLOR, n.to_string()),
+                    other => (LITERAL_COLOR, other.to_string()),
+                };
+                ui.colored_label(color, text);
+            });
+            return;
+        }
+    };
+
+    let title = match value {
+        Value::Object(_) => format!("{} {{{}}}", name, children.len()),
+        _ => format!("{} [{}]", name, children.len()),
+    };
+    egui::CollapsingHeader::new(title)
+        .id_source(path)
+        .default_open(depth < TREE_OPEN_DEPTH)
+        .show(ui, |ui| {
+            for (child_name, child) in children {
+                let child_path = format!("{}/{}", path, child_name);
+                show_json_node(ui, &child_path, &child_name, child, depth + 1);
+            }
+        });
+}
+
+/// Creates text layout with colored JSON keys, strings, numbers and literals
+pub fn highlight_json(text: &str, font_id: FontId) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut append = |part: &str, color: Color32| {
+        job.append(part, 0.0, TextFormat::simple(font_id.clone(), color));
+    };
+
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '"' => {
+                // Find closing quote skipping escaped characters
+                let mut escaped = false;
+                let end = rest[1..]
+                    .char_indices()
+                    .find(|(_, c)| {
+                        let closing = *c == '"' && !escaped;
+                        escaped = *c == '\\' && !escaped;
+                        closing
+                    })
+                    .map(|(i, _)| i + 2)
+                    .unwrap_or(rest.len());
+                let is_key = rest[end..].trim_start().starts_with(':');
+                append(&rest[..end], if is_key { KEY_COLOR } else { STRING_COLOR });
+                end
+            }
+            '-' | '0'..='9' => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                    .unwrap_or(rest.len());
+                append(&rest[..end], NUMBER_COLOR);
+                end
+            }
+            'a'..='z' => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(rest.len());
+                append(&rest[..end], LITERAL_COLOR);
+                end
+            }
+            _ => {
+                append(&rest[..c.len_utf8()], Color32::GRAY);
+                c.len_utf8()
+            }
+        };
+        rest = &rest[len..];
+    }
+    job
+}
+
+/// Single line preview of message key or payload. It is decoded once and drawn
+/// on every frame
+#[derive(Debug, Clone, PartialEq)]
+pub enum Preview {
+    Null,
+    Text(String),
+    Binary(String),
+    /// Hex of data the decoder failed to decode and the error
+    Invalid(String, String),
+    /// Decoder is loading the schema, data is decoded when it is loaded
+    Loading,
+}
+
+impl Preview {
+    /// Decodes preview limited to `max_chars` characters
+    pub fn new(data: Option<&[u8]>, decoder: &dyn MessageDecoder, max_chars: usize) -> Self {
+        match data {
+            Some(data) if !decoder.prefetch(data) => Preview::Loading,
+            Some(data) => Self::from_decoded(data, &decoder.decode(data), max_chars),
+            None => Preview::Null,
+        }
+    }
+
+    /// Creates preview of data already decoded
+    pub fn from_decoded(data: &[u8], decoded: &Result<Decoded>, max_chars: usize) -> Self {
+        match decoded {
+            Ok(decoded @ Decoded::Binary(_)) => Preview::Binary(decoded.preview(max_chars)),
+            Ok(decoded) => Preview::Text(decoded.preview(max_chars)),
+            Err(err) => Preview::Invalid(hex_preview(data, max_chars / 3), err.to_string()),
+        }
+    }
+
+    pub fn ui(&self, ui: &mut Ui) {
+        match self {
+            Preview::Null => ui.label(""),
+            Preview::Text(text) => ui.label(text),
+            Preview::Binary(hex) => ui.monospace(hex),
+            Preview::Invalid(hex, err) => ui.colored_label(Color32::RED, hex).on_hover_text(err),
+            Preview::Loading => ui.label(RichText::new("loading schema...").italics()),
+        };
+    }
+}
+
+fn size_text(data: Option<&[u8]>) -> String {
+    match data {
+        Some(data) => format!("{} bytes", data.len()),
+        None => "null".to_string(),
+    }
+}
+
+/// Formats data as lines of offset, hex bytes and printable ASCII characters
+pub fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, chunk) in data
+        .chunks(DUMP_LINE)
+        .take(MAX_DUMP_BYTES / DUMP_LINE)
+        .enumerate()
+    {
+        let _ = write!(dump, "{:08x}  ", line * DUMP_LINE);
+        for i in 0..DUMP_LINE {
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(dump, "{:02x} ", b);
+                }
+                None => dump.push_str("   "),
+            }
+            if i == DUMP_LINE / 2 - 1 {
+                dump.push(' ');
+            }
+        }
+        dump.push_str(" |");
+        dump.extend(chunk.iter().map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    if data.len() > MAX_DUMP_BYTES {
+        let _ = writeln!(dump, "... {} bytes more", data.len() - MAX_DUMP_BYTES);
+    }
+    dump
+}
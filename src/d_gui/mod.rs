@@ -1,14 +1,25 @@
+mod date_time;
+mod group_info;
+mod group_reset;
+mod message_detail;
 mod model;
+mod schema_info;
 mod server_info;
+mod topic_bulk;
 mod topic_info;
+mod topic_produce;
+mod topic_replay;
+mod topic_search;
 mod tree;
 
-use crate::d_gui::model::{KafkaTreePartition, KafkaTreeTopic};
+use crate::d_gui::group_info::GroupInfo;
+use crate::d_gui::model::{KafkaTreeGroup, KafkaTreePartition, KafkaTreeSubject, KafkaTreeTopic};
+use crate::d_gui::schema_info::SchemaInfo;
 use crate::d_gui::server_info::ServerInfo;
 use crate::d_gui::topic_info::TopicInfo;
 use crate::d_gui::tree::TreeNode;
 use crate::kafka::{AdminCommand, KafkaServer};
-use eframe::egui::{CentralPanel, CtxRef};
+use eframe::egui::{CentralPanel, Context};
 use eframe::epi::Frame;
 use eframe::{egui, epi};
 use log::info;
@@ -28,6 +39,10 @@ pub struct KatorApp {
     server_saved: bool,
     // Topics
     topic_info: TopicInfo,
+    // Consumer groups
+    group_info: GroupInfo,
+    // Schema Registry subjects
+    schema_info: SchemaInfo,
     // Servers
     server_info: ServerInfo,
     // Add topic
@@ -53,6 +68,8 @@ impl KatorApp {
             server_opened: false,
             server_saved: false,
             topic_info: TopicInfo::new(db.clone(), db_topics.clone()),
+            group_info: GroupInfo::new(db.clone()),
+            schema_info: SchemaInfo::new(db.clone()),
             server_info: ServerInfo::new(),
             topic_open: false,
             topic_name: "".to_string(),
@@ -68,7 +85,7 @@ impl KatorApp {
 }
 
 impl epi::App for KatorApp {
-    fn update(&mut self, ctx: &CtxRef, _frame: &Frame) {
+    fn update(&mut self, ctx: &Context, _frame: &Frame) {
         if self.servers.is_empty() {
             self.reload_servers();
         }
@@ -203,6 +220,20 @@ impl epi::App for KatorApp {
                 self.topic_info.set_topic(&topic).ui(ui);
             });
         }
+
+        // Show consumer group information
+        if let Some(group) = self.selected_group() {
+            CentralPanel::default().show(ctx, |ui| {
+                self.group_info.set_group(&group).ui(ui);
+            });
+        }
+
+        // Show schema registry subject
+        if let Some(subject) = self.selected_subject() {
+            CentralPanel::default().show(ctx, |ui| {
+                self.schema_info.set_subject(&subject).ui(ui);
+            });
+        }
     }
 
     fn name(&self) -> &str {
@@ -213,7 +244,7 @@ impl epi::App for KatorApp {
 impl KatorApp {
     fn selected_server(&self) -> Option<KafkaServer> {
         self.tree.selected().and_then(|node| match node {
-            TreeNode::Server(server, ..) => Some(server.clone()),
+            TreeNode::Server(server, ..) => Some(server.as_ref().clone()),
             _ => None,
         })
     }
@@ -225,6 +256,20 @@ impl KatorApp {
         })
     }
 
+    fn selected_group(&self) -> Option<KafkaTreeGroup> {
+        self.tree.selected().and_then(|node| match node {
+            TreeNode::Group(group, ..) => Some(group.clone()),
+            _ => None,
+        })
+    }
+
+    fn selected_subject(&self) -> Option<KafkaTreeSubject> {
+        self.tree.selected().and_then(|node| match node {
+            TreeNode::Subject(subject) => Some(subject.clone()),
+            _ => None,
+        })
+    }
+
     fn selected_partition(&self) -> Option<KafkaTreePartition> {
         self.tree.selected().and_then(|node| match node {
             TreeNode::Partition(p, ..) => Some(p.clone()),
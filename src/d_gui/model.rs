@@ -1,3 +1,4 @@
+use rdkafka::groups::GroupInfo;
 use rdkafka::metadata::{MetadataBroker, MetadataTopic};
 use std::fmt::{Display, Formatter};
 
@@ -84,3 +85,60 @@ impl KafkaTreeTopic {
         &self.partitions
     }
 }
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct KafkaTreeGroupMember {
+    pub id: String,
+    pub client_id: String,
+    pub host: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct KafkaTreeGroup {
+    pub server_name: String,
+    pub name: String,
+    pub state: String,
+    pub protocol_type: String,
+    pub protocol: String,
+    pub members: Vec<KafkaTreeGroupMember>,
+}
+
+impl Display for KafkaTreeGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl KafkaTreeGroup {
+    pub fn from_group<T: AsRef<str>>(server_name: T, group: &GroupInfo) -> Self {
+        Self {
+            server_name: server_name.as_ref().to_string(),
+            name: group.name().to_string(),
+            state: group.state().to_string(),
+            protocol_type: group.protocol_type().to_string(),
+            protocol: group.protocol().to_string(),
+            members: group
+                .members()
+                .iter()
+                .map(|m| KafkaTreeGroupMember {
+                    id: m.id().to_string(),
+                    client_id: m.client_id().to_string(),
+                    host: m.client_host().to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Subject of the server Schema Registry
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct KafkaTreeSubject {
+    pub server_name: String,
+    pub name: String,
+}
+
+impl Display for KafkaTreeSubject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
@@ -0,0 +1,276 @@
+use crate::codec::{RegisteredSchema, SchemaRegistry};
+use crate::d_gui::model::KafkaTreeSubject;
+use crate::kafka::KafkaServer;
+use anyhow::Result as AnyResult;
+use eframe::egui;
+use eframe::egui::text::{LayoutJob, TextFormat};
+use eframe::egui::{Color32, TextStyle, Ui};
+use log::error;
+use similar::{ChangeTag, TextDiff};
+use sled::Tree as DbTree;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Panel with Schema Registry subject: compatibility level, versions, schema text
+/// with references and difference between two versions
+#[derive(Debug)]
+pub struct SchemaInfo {
+    db: Rc<DbTree>,
+    subject: KafkaTreeSubject,
+    registry: Option<SchemaRegistry>,
+    compatibility: String,
+    versions: Vec<i32>,
+    version: i32,
+    schema: Option<Arc<RegisteredSchema>>,
+    /// Version compared with the selected one
+    diff_version: i32,
+    diff: Vec<(ChangeTag, String)>,
+}
+
+/// Schema text for display. JSON based schemas are pretty printed,
+/// so the registry compact form is readable and diffs are line based
+fn schema_text(schema: &RegisteredSchema) -> String {
+    serde_json::from_str::<serde_json::Value>(&schema.schema)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| schema.schema.clone())
+}
+
+impl SchemaInfo {
+    pub fn new(db: Rc<DbTree>) -> Self {
+        Self {
+            db,
+            subject: KafkaTreeSubject {
+                server_name: "".to_string(),
+                name: "".to_string(),
+            },
+            registry: None,
+            compatibility: "".to_string(),
+            versions: vec![],
+            version: 0,
+            schema: None,
+            diff_version: 0,
+            diff: vec![],
+        }
+    }
+
+    pub fn set_subject(&mut self, subject: &KafkaTreeSubject) -> &mut Self {
+        if &self.subject != subject {
+            let server_changed = self.subject.server_name != subject.server_name;
+            self.subject = subject.clone();
+            if server_changed || self.registry.is_none() {
+                self.registry = match KafkaServer::from_db(&self.db, &subject.server_name)
+                    .and_then(|server| SchemaRegistry::new(&server.schema_registry))
+                {
+                    Ok(registry) => Some(registry),
+                    Err(err) => {
+                        error!(
+                            "Error connecting to schema registry of {}: {}",
+                            &subject.server_name, err
+                        );
+                        None
+                    }
+                };
+            }
+            self.refresh();
+        }
+
+        self
+    }
+
+    /// Reloads compatibility level and versions, the latest version is selected
+    fn refresh(&mut self) {
+        if let Err(err) = self.read_subject() {
+            error!("Error reading subject {}: {}", &self.subject.name, err);
+        }
+    }
+
+    fn read_subject(&mut self) -> AnyResult<()> {
+        self.compatibility.clear();
+        self.versions.clear();
+        self.schema = None;
+        self.diff.clear();
+        if let Some(registry) = &self.registry {
+            self.compatibility = registry.compatibility(&self.subject.name)?;
+            self.versions = registry.versions(&self.subject.name)?;
+            self.version = self.versions.last().copied().unwrap_or_default();
+            // Compare with the previous version by default
+            self.diff_version = self
+                .versions
+                .iter()
+                .rev()
+                .nth(1)
+                .copied()
+                .unwrap_or(self.version);
+            self.schema = Some(registry.schema_by_version(&self.subject.name, self.version)?);
+            self.update_diff()?;
+        }
+        Ok(())
+    }
+
+    fn update_diff(&mut self) -> AnyResult<()> {
+        self.diff.clear();
+        if let Some(registry) = &self.registry {
+            let old = registry.schema_by_version(&self.subject.name, self.diff_version)?;
+            let new = registry.schema_by_version(&self.subject.name, self.version)?;
+            let (old, new) = (schema_text(&old), schema_text(&new));
+            self.diff = TextDiff::from_lines(&old, &new)
+                .iter_all_changes()
+                .map(|change| {
+                    let mut line = change.to_string();
+                    if !line.ends_with('\n') {
+                        line.push('\n');
+                    }
+                    (change.tag(), line)
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn select_version(&mut self) {
+        let result = match &self.registry {
+            Some(registry) => registry
+                .schema_by_version(&self.subject.name, self.version)
+                .map(|schema| self.schema = Some(schema))
+                .and_then(|_| self.update_diff()),
+            None => Ok(()),
+        };
+        if let Err(err) = result {
+            error!(
+                "Error reading version {} of subject {}: {}",
+                self.version, &self.subject.name, err
+            );
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("refresh").clicked() {
+                self.refresh();
+            }
+        });
+        ui.separator();
+
+        let (version, diff_version) = (self.version, self.diff_version);
+        egui::Grid::new("schema_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Subject");
+                ui.label(&self.subject.name);
+                ui.end_row();
+
+                ui.label("Compatibility");
+                ui.label(&self.compatibility);
+                ui.end_row();
+
+                ui.label("Version");
+                egui::ComboBox::from_id_source("schema_version")
+                    .selected_text(self.version.to_string())
+                    .show_ui(ui, |ui| {
+                        for v in &self.versions {
+                            ui.selectable_value(&mut self.version, *v, v.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                if let Some(schema) = &self.schema {
+                    ui.label("Schema ID");
+                    ui.label(schema.id.map(|id| id.to_string()).unwrap_or_default());
+                    ui.end_row();
+
+                    ui.label("Type");
+                    ui.label(&schema.schema_type);
+                    ui.end_row();
+                }
+            });
+        if version != self.version {
+            self.select_version();
+        }
+
+        let schema = match &self.schema {
+            Some(schema) => schema.clone(),
+            None => return,
+        };
+
+        egui::CollapsingHeader::new(format!("References ({})", schema.references.len()))
+            .default_open(!schema.references.is_empty())
+            .show(ui, |ui| {
+                egui::Grid::new("schema_references_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Name");
+                        ui.strong("Subject");
+                        ui.strong("Version");
+                        ui.end_row();
+
+                        for reference in &schema.references {
+                            ui.label(&reference.name);
+                            ui.label(&reference.subject);
+                            ui.label(reference.version.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        egui::CollapsingHeader::new("Schema")
+            .default_open(true)
+            .show(ui, |ui| {
+                let mut text = schema_text(&schema);
+                egui::ScrollArea::vertical()
+                    .id_source("schema_text")
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut text)
+                                .code_editor()
+                                .lock_focus(true)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            });
+
+        egui::CollapsingHeader::new("Diff")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("from version");
+                    egui::ComboBox::from_id_source("schema_diff_version")
+                        .selected_text(self.diff_version.to_string())
+                        .show_ui(ui, |ui| {
+                            for v in &self.versions {
+                                ui.selectable_value(&mut self.diff_version, *v, v.to_string());
+                            }
+                        });
+                    ui.label(format!("to version {}", self.version));
+                });
+
+                let font_id = TextStyle::Monospace.resolve(ui.style());
+                let mut job = LayoutJob::default();
+                for (tag, line) in &self.diff {
+                    let (sign, color) = match tag {
+                        ChangeTag::Delete => ("-", Color32::RED),
+                        ChangeTag::Insert => ("+", Color32::GREEN),
+                        ChangeTag::Equal => (" ", Color32::GRAY),
+                    };
+                    job.append(
+                        &format!("{} {}", sign, line),
+                        0.0,
+                        TextFormat::simple(font_id.clone(), color),
+                    );
+                }
+                egui::ScrollArea::vertical()
+                    .id_source("schema_diff")
+                    .max_height(400.0)
+                    .show(ui, |ui| ui.label(job));
+            });
+        if diff_version != self.diff_version {
+            if let Err(err) = self.update_diff() {
+                error!("Error comparing versions: {}", err);
+            }
+        }
+    }
+}
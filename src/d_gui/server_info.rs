@@ -1,4 +1,4 @@
-use crate::kafka::{KafkaServer, Password};
+use crate::kafka::{IsolationLevel, KafkaServer, Partitioner, Password, SchemaRegistryConfig};
 use eframe::egui;
 use eframe::egui::CursorIcon::Default;
 use eframe::egui::{Label, TextEdit, Ui};
@@ -8,6 +8,8 @@ pub struct ServerInfo {
     server: KafkaServer,
     ssl_keystore_location: String,
     password: String,
+    registry_password: String,
+    registry_keystore_password: String,
 }
 
 impl ServerInfo {
@@ -19,6 +21,8 @@ impl ServerInfo {
                 .clone()
                 .unwrap_or("".to_string()),
             password: "".to_string(),
+            registry_password: "".to_string(),
+            registry_keystore_password: "".to_string(),
         }
     }
 
@@ -40,9 +44,14 @@ impl ServerInfo {
                 ssl_security_protocol: "PLAINTEXT".to_string(),
                 message_max_bytes: 100,
                 fetch_max_bytes: 102400,
+                isolation_level: IsolationLevel::default(),
+                partitioner: Partitioner::default(),
+                schema_registry: SchemaRegistryConfig::default(),
             },
             ssl_keystore_location: "".to_string(),
             password: "".to_string(),
+            registry_password: "".to_string(),
+            registry_keystore_password: "".to_string(),
         }
     }
 
@@ -56,6 +65,15 @@ impl ServerInfo {
             ks.ssl_keystore_password = Some(Password::new(&self.password).unwrap());
         }
 
+        if !self.registry_password.is_empty() {
+            ks.schema_registry.password = Some(Password::new(&self.registry_password).unwrap());
+        }
+
+        if !self.registry_keystore_password.is_empty() {
+            ks.schema_registry.ssl_keystore_password =
+                Some(Password::new(&self.registry_keystore_password).unwrap());
+        }
+
         ks
     }
 
@@ -100,6 +118,38 @@ impl ServerInfo {
         );
         ui.end_row();
 
+        ui.label("Isolation level")
+            .on_hover_text("Visibility of messages written in transactions");
+        egui::ComboBox::from_id_source("isolation_level")
+            .selected_text(self.server.isolation_level.to_string())
+            .show_ui(ui, |ui| {
+                [
+                    IsolationLevel::ReadUncommitted,
+                    IsolationLevel::ReadCommitted,
+                ]
+                .into_iter()
+                .for_each(|level| {
+                    ui.selectable_value(&mut self.server.isolation_level, level, level.to_string());
+                });
+            });
+        ui.end_row();
+
+        ui.label("Partitioner").on_hover_text(
+            "Partition of messages produced without one, murmur2_random matches Java clients",
+        );
+        egui::ComboBox::from_id_source("partitioner")
+            .selected_text(self.server.partitioner.to_string())
+            .show_ui(ui, |ui| {
+                for partitioner in Partitioner::ALL {
+                    ui.selectable_value(
+                        &mut self.server.partitioner,
+                        partitioner,
+                        partitioner.to_string(),
+                    );
+                }
+            });
+        ui.end_row();
+
         ui.label("Security protocol");
         egui::ComboBox::from_label("")
             .selected_text(self.server.ssl_security_protocol.clone())
@@ -134,5 +184,61 @@ impl ServerInfo {
         )
         .on_hover_text("Define keystore password");
         ui.end_row();
+
+        ui.label("Schema Registry URL");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.server.schema_registry.url)
+                .interactive(enabled)
+                .hint_text("http://localhost:8081"),
+        )
+        .on_hover_text("Schema Registry is used to decode Avro and Protobuf messages");
+        ui.end_row();
+
+        ui.label("Registry user");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.server.schema_registry.username)
+                .interactive(enabled)
+                .hint_text("user"),
+        )
+        .on_hover_text("User for basic authentication");
+        ui.end_row();
+
+        ui.label("Registry password");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.registry_password)
+                .interactive(enabled)
+                .password(true)
+                .hint_text("password"),
+        )
+        .on_hover_text("Password for basic authentication");
+        ui.end_row();
+
+        ui.label("Registry CA location");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.server.schema_registry.ssl_ca_location)
+                .interactive(enabled)
+                .hint_text("ca.pem"),
+        )
+        .on_hover_text("CA certificate in PEM format");
+        ui.end_row();
+
+        ui.label("Registry keystore location");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.server.schema_registry.ssl_keystore_location)
+                .interactive(enabled)
+                .hint_text("keystore"),
+        )
+        .on_hover_text("Client keystore in PKCS#12 format");
+        ui.end_row();
+
+        ui.label("Registry keystore password");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.registry_keystore_password)
+                .interactive(enabled)
+                .password(true)
+                .hint_text("password"),
+        )
+        .on_hover_text("Password of the registry client keystore");
+        ui.end_row();
     }
 }
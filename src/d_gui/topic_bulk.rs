@@ -0,0 +1,346 @@
+use crate::codec::{EncoderRegistry, MessageEncoder};
+use crate::d_gui::model::KafkaTreeTopic;
+use crate::d_gui::topic_produce::format_combo;
+use crate::kafka::{
+    parse_records, BulkEvent, BulkFormat, BulkMessage, BulkProgress, BulkSender, KafkaServer,
+    ProducerCommand,
+};
+use anyhow::{anyhow, Result as AnyResult};
+use eframe::egui;
+use eframe::egui::{Color32, Ui};
+use std::fs;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+
+/// Number of file records encoded before they are passed to the producer
+const ENCODE_BATCH: usize = 1000;
+
+/// Channels of the background bulk produce worker. Worker stops when this structure is dropped
+#[derive(Debug)]
+struct BulkJob {
+    commands: Sender<ProducerCommand>,
+    events: Receiver<BulkEvent>,
+}
+
+impl Drop for BulkJob {
+    fn drop(&mut self) {
+        let _ = self.commands.send(ProducerCommand::Stop);
+    }
+}
+
+/// Bulk produce worker with its progress and errors, used by panels sending many messages
+#[derive(Debug)]
+pub struct BulkRun {
+    job: Option<BulkJob>,
+    progress: BulkProgress,
+    errors: Vec<String>,
+    status: Result<String, String>,
+}
+
+impl BulkRun {
+    pub fn new() -> Self {
+        Self {
+            job: None,
+            progress: BulkProgress::default(),
+            errors: vec![],
+            status: Ok("".to_string()),
+        }
+    }
+
+    /// Cancels running worker and clears progress
+    pub fn reset(&mut self) {
+        self.job = None;
+        self.progress = BulkProgress::default();
+        self.errors.clear();
+        self.status = Ok("".to_string());
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Tracks the worker started by `start`, the error of starting is shown as status
+    pub fn start<F>(&mut self, status: &str, start: F)
+    where
+        F: FnOnce() -> AnyResult<(Sender<ProducerCommand>, Receiver<BulkEvent>)>,
+    {
+        match start() {
+            Ok((commands, events)) => {
+                self.job = Some(BulkJob { commands, events });
+                self.status = Ok(status.to_string());
+            }
+            Err(err) => self.status = Err(err.to_string()),
+        }
+    }
+
+    fn summary(&self, finished: &str) -> String {
+        format!(
+            "{} in {:.1}s: {} delivered, {} failed, {} skipped",
+            finished,
+            self.progress.elapsed.as_secs_f32(),
+            self.progress.delivered,
+            self.progress.failed,
+            self.progress.skipped
+        )
+    }
+
+    fn receive(&mut self) {
+        if let Some(job) = &self.job {
+            let mut finished = false;
+            for event in job.events.try_iter() {
+                match event {
+                    BulkEvent::Progress(progress) => self.progress = progress,
+                    BulkEvent::Error(source, err) => {
+                        self.errors.push(format!("{}: {}", source, err));
+                    }
+                    BulkEvent::Finished(progress) => {
+                        self.progress = progress;
+                        finished = true;
+                    }
+                }
+            }
+
+            if finished {
+                self.job = None;
+                self.status = Ok(self.summary("Finished"));
+            }
+        }
+    }
+
+    /// Shows the send button or the cancel button when the worker is running.
+    /// Returns true when send is clicked
+    pub fn buttons_ui(&mut self, ui: &mut Ui, ready: bool) -> bool {
+        self.receive();
+        if self.job.is_some() {
+            ui.ctx().request_repaint();
+            if ui.button("cancel").clicked() {
+                self.job = None;
+                self.status = Ok(self.summary("Cancelled"));
+            }
+            false
+        } else {
+            ui.add_enabled(ready, egui::Button::new("send")).clicked()
+        }
+    }
+
+    pub fn status_ui(&self, ui: &mut Ui) {
+        match &self.status {
+            Ok(text) => ui.label(text),
+            Err(text) => ui.colored_label(Color32::RED, text),
+        };
+    }
+
+    /// Shows progress bar and errors of sent messages
+    pub fn progress_ui(&self, ui: &mut Ui, id: &str) {
+        if self.progress.total > 0 {
+            ui.add(
+                egui::ProgressBar::new(self.progress.fraction())
+                    .text(format!(
+                        "{} / {} sent, {} delivered, {} failed",
+                        self.progress.sent,
+                        self.progress.total,
+                        self.progress.delivered,
+                        self.progress.failed
+                    ))
+                    .desired_width(400.0),
+            );
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .id_source(id)
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for err in &self.errors {
+                    ui.colored_label(Color32::RED, err);
+                }
+            });
+    }
+}
+
+/// Panel sending messages of a JSONL or CSV file to the topic. Key and value columns
+/// are converted by the encoders of the selected formats
+#[derive(Debug)]
+pub struct TopicBulk {
+    path: String,
+    format: BulkFormat,
+    key_format: String,
+    payload_format: String,
+    /// Messages per second, 0 is unlimited
+    rate: u32,
+    run: BulkRun,
+}
+
+impl TopicBulk {
+    pub fn new() -> Self {
+        Self {
+            path: "".to_string(),
+            format: BulkFormat::JsonLines,
+            key_format: "String".to_string(),
+            payload_format: "String".to_string(),
+            rate: 100,
+            run: BulkRun::new(),
+        }
+    }
+
+    /// Cancels running produce and selects key and payload formats of the topic preferences
+    pub fn set_formats(&mut self, key_format: &str, payload_format: &str) {
+        self.run.reset();
+        self.key_format = key_format.to_string();
+        self.payload_format = payload_format.to_string();
+    }
+
+    fn start(&mut self, encoders: &EncoderRegistry, server: &KafkaServer, topic: &KafkaTreeTopic) {
+        self.run.reset();
+        let path = self.path.clone();
+        let format = self.format;
+        let key = FormatEncoder::new(encoders, &self.key_format);
+        let payload = FormatEncoder::new(encoders, &self.payload_format);
+        let rate = self.rate;
+        self.run.start("Sending...", || {
+            Ok(server
+                .open()?
+                .create_producer()?
+                .start_bulk(&topic.name, rate, move |sender| {
+                    produce_file(sender, &path, format, &key, &payload)
+                }))
+        });
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        encoders: &EncoderRegistry,
+        server: &KafkaServer,
+        topic: &KafkaTreeTopic,
+    ) {
+        ui.horizontal(|ui| {
+            if self.run.buttons_ui(ui, !self.path.is_empty()) {
+                self.start(encoders, server, topic);
+            }
+
+            ui.label("messages/sec");
+            ui.add(egui::DragValue::new(&mut self.rate).clamp_range(0..=1000000))
+                .on_hover_text("0 sends without limit");
+        });
+
+        self.run.status_ui(ui);
+        ui.separator();
+
+        ui.add_enabled_ui(!self.run.is_running(), |ui| {
+            egui::Grid::new("bulk_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("File");
+                    if ui
+                        .text_edit_singleline(&mut self.path)
+                        .on_hover_text("Columns: key, value, headers, partition, timestamp")
+                        .changed()
+                    {
+                        self.format = BulkFormat::from_path(&self.path);
+                    }
+                    ui.end_row();
+
+                    ui.label("Format");
+                    egui::ComboBox::from_id_source("bulk_format")
+                        .selected_text(self.format.to_string())
+                        .show_ui(ui, |ui| {
+                            for format in [BulkFormat::JsonLines, BulkFormat::Csv] {
+                                ui.selectable_value(&mut self.format, format, format.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Key");
+                    format_combo(ui, "bulk_key_format", &mut self.key_format, encoders);
+                    ui.end_row();
+
+                    ui.label("Value");
+                    format_combo(
+                        ui,
+                        "bulk_payload_format",
+                        &mut self.payload_format,
+                        encoders,
+                    );
+                    ui.end_row();
+                });
+        });
+
+        self.run.progress_ui(ui, "bulk_errors");
+    }
+}
+
+/// Encoder of the format selected for keys or values of the file
+#[derive(Debug)]
+struct FormatEncoder {
+    format: String,
+    /// `None` when messages of the format can't be produced
+    encoder: Option<Arc<dyn MessageEncoder>>,
+}
+
+impl FormatEncoder {
+    fn new(encoders: &EncoderRegistry, format: &str) -> Self {
+        Self {
+            format: format.to_string(),
+            encoder: encoders.shared(format),
+        }
+    }
+
+    fn encode(&self, text: &Option<String>) -> AnyResult<Option<Vec<u8>>> {
+        match (text, &self.encoder) {
+            (None, _) => Ok(None),
+            (Some(text), Some(encoder)) => encoder.encode(text).map(Some),
+            (Some(_), None) => Err(anyhow!("{} messages can't be produced", self.format)),
+        }
+    }
+}
+
+/// Reads the file, encodes its records and sends them in batches. It runs in the bulk
+/// produce worker, invalid records are reported and skipped
+fn produce_file(
+    sender: &mut BulkSender<'_>,
+    path: &str,
+    format: BulkFormat,
+    key: &FormatEncoder,
+    payload: &FormatEncoder,
+) {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => return sender.error(path.to_string(), err.to_string()),
+    };
+    let (records, errors) = parse_records(&text, format);
+    sender.expect(records.len() + errors.len());
+    for err in errors {
+        sender.skip(format!("line {}", err.line), err.reason);
+    }
+
+    let mut messages = Vec::with_capacity(ENCODE_BATCH);
+    for record in records {
+        let encoded = key
+            .encode(&record.key)
+            .and_then(|key| payload.encode(&record.value).map(|payload| (key, payload)));
+        match encoded {
+            Ok((key, payload)) => messages.push(BulkMessage {
+                source: format!("line {}", record.line),
+                key,
+                payload,
+                headers: record
+                    .headers
+                    .into_iter()
+                    .map(|(name, value)| (name, value.into_bytes()))
+                    .collect(),
+                partition: record.partition,
+                timestamp: record.timestamp,
+            }),
+            Err(err) => sender.skip(format!("line {}", record.line), err.to_string()),
+        }
+        if messages.len() == ENCODE_BATCH {
+            if !sender.send(&messages) {
+                return;
+            }
+            messages.clear();
+        }
+    }
+    sender.send(&messages);
+}
@@ -1,27 +1,49 @@
+use crate::codec::{
+    load_descriptors, message_names, AvroDecoder, BinaryDecoder, Compression, DecoderRegistry,
+    Decompressing, EncoderRegistry, MessageDecoder, ProtobufDecoder, ProtobufEncoder,
+    RegistryProtobufDecoder, SchemaRegistry,
+};
+use crate::d_gui::date_time::{format_timestamp, DateTimeEdit};
+use crate::d_gui::message_detail::{MessageDetail, Preview};
 use crate::d_gui::model::KafkaTreeTopic;
-use crate::kafka::{AdminCommand, AdminOps, ConsumerOps, KafkaServer, ProducerOps};
-use anyhow::Result as AnyResult;
+use crate::d_gui::topic_bulk::TopicBulk;
+use crate::d_gui::topic_produce::TopicProduce;
+use crate::d_gui::topic_replay::TopicReplay;
+use crate::d_gui::topic_search::TopicSearch;
+use crate::kafka::{
+    merge_by_timestamp, AbortedCheck, AdminCommand, AdminOps, ConsumerCommand, ConsumerOps,
+    IsolationLevel, KafkaOpsError, KafkaServer, MessageFilter, ProducerOps,
+};
+use anyhow::{anyhow, Result as AnyResult};
 use eframe::egui;
-use eframe::egui::Ui;
+use eframe::egui::{Color32, Ui};
 use futures::executor;
 use log::{error, info};
 use rdkafka::admin::TopicReplication;
-use rdkafka::message::{Headers, OwnedMessage};
+use rdkafka::message::OwnedMessage;
 use rdkafka::statistics::Topic;
 use rdkafka::Message;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sled::Tree as DbTree;
-use std::fmt::{Display, Formatter, Write};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::rc::Rc;
-use std::str::{from_utf8, from_utf8_unchecked};
-use std::sync::mpsc::Sender;
-use std::time;
+use std::str::from_utf8_unchecked;
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::{cmp, time};
 use tracing_subscriber::fmt::format;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum InfoPanel {
     Properties,
     Data,
+    Produce,
+    Bulk,
+    Replay,
+    Search,
 }
 
 impl Display for InfoPanel {
@@ -29,6 +51,10 @@ impl Display for InfoPanel {
         match self {
             InfoPanel::Properties => f.write_str("Properties"),
             InfoPanel::Data => f.write_str("Data"),
+            InfoPanel::Produce => f.write_str("Produce"),
+            InfoPanel::Bulk => f.write_str("Bulk produce"),
+            InfoPanel::Replay => f.write_str("Copy"),
+            InfoPanel::Search => f.write_str("Search"),
         }
     }
 }
@@ -38,6 +64,8 @@ enum MessagesOffset {
     FromTail,
     FromStart,
     FromOffset,
+    FromTime,
+    FromRange,
 }
 
 impl Display for MessagesOffset {
@@ -46,10 +74,32 @@ impl Display for MessagesOffset {
             MessagesOffset::FromTail => f.write_str("Tail"),
             MessagesOffset::FromStart => f.write_str("Start"),
             MessagesOffset::FromOffset => f.write_str("Offset"),
+            MessagesOffset::FromTime => f.write_str("Time"),
+            MessagesOffset::FromRange => f.write_str("Range"),
         }
     }
 }
 
+/// Page navigation through a single partition
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum PageMove {
+    First,
+    Previous,
+    Next,
+    Last,
+    Offset(i64),
+}
+
+/// Offsets of messages shown in the data list and available offsets range
+/// of the partition at the moment of reading
+#[derive(Debug, Copy, Clone)]
+struct PageWindow {
+    start: i64,
+    end: i64,
+    low: i64,
+    high: i64,
+}
+
 #[derive(Debug)]
 pub struct TopicInfo {
     db: Rc<DbTree>,
@@ -60,13 +110,80 @@ pub struct TopicInfo {
     partition_len: i32,
     replication_factor: i32,
     topic_pref: TopicPref,
+    decoders: DecoderRegistry,
+    encoders: EncoderRegistry,
+    /// Message types of the topic proto file
+    proto_messages: Result<Vec<String>, String>,
+    detail: MessageDetail,
     filter: String,
+    message_filter: Result<MessageFilter, String>,
     offset_type: MessagesOffset,
-    offset: i32,
+    offset: i64,
+    range_end: i64,
+    timestamp: DateTimeEdit,
     partition: i32,
     ops: Option<TopicOps>,
     data: Vec<OwnedMessage>,
+    /// Errors of the last read, partitions which failed are not shown
+    read_errors: Vec<String>,
     selected_data: usize,
+    page: Option<PageWindow>,
+    jump_offset: i64,
+    isolation: IsolationLevel,
+    aborted: HashSet<(i32, i64)>,
+    /// Results of the background search of aborted messages
+    aborted_check: Option<Receiver<(i32, AnyResult<Vec<i64>>)>>,
+    /// Decoded data list rows by partition and offset
+    previews: HashMap<(i32, i64), RowPreview>,
+    /// Key format, data format and compression the previews are decoded with
+    preview_formats: (String, String, Compression),
+    live: Option<LiveTail>,
+    search: TopicSearch,
+    produce: TopicProduce,
+    bulk: TopicBulk,
+    replay: TopicReplay,
+    /// Key entered in the partition calculator
+    partition_key: String,
+}
+
+const FILTER_HELP: &str = r#"Filter expression, for example:
+key == "k1", key contains "k", key ~ "^k[0-9]+"
+value contains "error", value ~ "id=\d+"
+header("type") == "order", header("trace-id")
+partition == 1, offset >= 100, offset in 100..200
+$.order.id == "A-1", $.items[0].price > 10, $.tags contains "new", $["a b"] == 1
+JSON paths are evaluated on the payload decoded by the Data format
+Combine with and, or, not and parentheses"#;
+
+/// Maximum number of messages waiting in the live tail channel
+const LIVE_BUFFER_SIZE: usize = 1000;
+
+/// Channels of the background consumer worker used in live tail mode.
+/// Worker stops when this structure is dropped
+#[derive(Debug)]
+struct LiveTail {
+    commands: Sender<ConsumerCommand>,
+    messages: Receiver<OwnedMessage>,
+    paused: bool,
+}
+
+impl LiveTail {
+    fn toggle_pause(&mut self) {
+        let cmd = if self.paused {
+            ConsumerCommand::Resume
+        } else {
+            ConsumerCommand::Pause
+        };
+        if self.commands.send(cmd).is_ok() {
+            self.paused = !self.paused;
+        }
+    }
+}
+
+impl Drop for LiveTail {
+    fn drop(&mut self) {
+        let _ = self.commands.send(ConsumerCommand::Stop);
+    }
 }
 
 struct KMsg(OwnedMessage);
@@ -76,22 +193,79 @@ impl PartialEq for KMsg {
     }
 }
 
+/// Key and payload of a data list row decoded by the topic formats
+#[derive(Debug)]
+struct RowPreview {
+    key: Preview,
+    payload: Preview,
+    /// Payload document for JSON path filters
+    json: Option<Value>,
+}
+
+impl RowPreview {
+    fn new(
+        msg: &OwnedMessage,
+        key_decoder: &dyn MessageDecoder,
+        payload_decoder: &dyn MessageDecoder,
+    ) -> Self {
+        let (payload, json) = match msg.payload() {
+            Some(data) if !payload_decoder.prefetch(data) => (Preview::Loading, None),
+            Some(data) => {
+                let decoded = payload_decoder.decode(data);
+                let payload = Preview::from_decoded(data, &decoded, 100);
+                (payload, decoded.ok().and_then(|d| d.into_json()))
+            }
+            None => (Preview::Null, None),
+        };
+        Self {
+            key: Preview::new(msg.key(), key_decoder, 48),
+            payload,
+            json,
+        }
+    }
+
+    /// Returns true while schemas of the key or payload are loaded
+    fn is_loading(&self) -> bool {
+        self.key == Preview::Loading || self.payload == Preview::Loading
+    }
+}
+
 #[derive(Debug)]
 struct TopicOps {
+    /// Consumer with read_uncommitted isolation level, it returns all messages
     consumer: ConsumerOps,
+    /// Consumer with read_committed isolation level
+    committed: ConsumerOps,
     producer: ProducerOps,
     admin: Sender<AdminCommand>,
+    /// Worker searching aborted transactions with its own read_committed consumer
+    aborted: Sender<AbortedCheck>,
 }
 
 impl TopicOps {
     pub fn from_server(server: &KafkaServer) -> AnyResult<Self> {
-        let kafka_ops = server.open()?;
+        let with_level = |isolation_level| KafkaServer {
+            isolation_level,
+            ..server.clone()
+        };
+        let kafka_ops = with_level(IsolationLevel::ReadUncommitted).open()?;
+        let committed_ops = with_level(IsolationLevel::ReadCommitted).open()?;
         Ok(Self {
             consumer: kafka_ops.create_consumer()?,
+            committed: committed_ops.create_consumer()?,
             producer: kafka_ops.create_producer()?,
             admin: kafka_ops.create_admin()?.start_worker(),
+            aborted: committed_ops.create_consumer()?.start_aborted_worker(),
         })
     }
+
+    /// Returns consumer reading messages with the given isolation level
+    fn reader(&self, isolation: IsolationLevel) -> &ConsumerOps {
+        match isolation {
+            IsolationLevel::ReadUncommitted => &self.consumer,
+            IsolationLevel::ReadCommitted => &self.committed,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -99,16 +273,28 @@ struct TopicPref {
     key: String,
     key_format: String,
     data_format: String,
+    /// Compression of the payload applied before the data format
+    #[serde(default)]
+    data_compression: Compression,
     last_messages: u32,
+    /// `.proto` file or compiled `FileDescriptorSet` used by the Protobuf decoder
+    #[serde(default)]
+    proto_file: String,
+    /// Full name of the protobuf message type
+    #[serde(default)]
+    proto_message: String,
 }
 
 impl TopicPref {
     pub fn from_db<T: AsRef<str>>(name: T, db: &DbTree) -> Self {
         let empty = Self {
             key: name.as_ref().to_string(),
-            key_format: "Binary".to_string(),
-            data_format: "Binary".to_string(),
+            key_format: BinaryDecoder.name().to_string(),
+            data_format: BinaryDecoder.name().to_string(),
+            data_compression: Compression::None,
             last_messages: 200,
+            proto_file: "".to_string(),
+            proto_message: "".to_string(),
         };
 
         match db.get(name.as_ref()) {
@@ -133,6 +319,22 @@ impl TopicPref {
     }
 }
 
+/// Creates decoders available for topics of the server
+fn create_decoders(server: &KafkaServer) -> DecoderRegistry {
+    let mut decoders = DecoderRegistry::new();
+    if !server.schema_registry.url.is_empty() {
+        match SchemaRegistry::new(&server.schema_registry) {
+            Ok(registry) => {
+                let registry = Arc::new(registry);
+                decoders.register(Box::new(AvroDecoder::new(registry.clone())));
+                decoders.register(Box::new(RegistryProtobufDecoder::new(registry)));
+            }
+            Err(err) => error!("Error creating schema registry client: {}", err),
+        }
+    }
+    decoders
+}
+
 impl TopicInfo {
     pub fn new(db: Rc<DbTree>, db_topics: Rc<DbTree>) -> Self {
         Self {
@@ -149,17 +351,41 @@ impl TopicInfo {
             replication_factor: -1,
             topic_pref: TopicPref {
                 key: "default".to_string(),
-                key_format: "Binary".to_string(),
-                data_format: "Binary".to_string(),
+                key_format: BinaryDecoder.name().to_string(),
+                data_format: BinaryDecoder.name().to_string(),
+                data_compression: Compression::None,
                 last_messages: 200,
+                proto_file: "".to_string(),
+                proto_message: "".to_string(),
             },
+            proto_messages: Ok(vec![]),
+            decoders: DecoderRegistry::new(),
+            encoders: EncoderRegistry::new(),
+            detail: MessageDetail::new(),
             filter: "".to_string(),
+            message_filter: MessageFilter::parse("").map_err(|e| e.to_string()),
             offset_type: MessagesOffset::FromTail,
             offset: 0,
+            range_end: 0,
+            timestamp: DateTimeEdit::now(),
             partition: -1,
             ops: None,
             data: vec![],
-            selected_data: 1,
+            read_errors: vec![],
+            selected_data: 0,
+            page: None,
+            jump_offset: 0,
+            isolation: IsolationLevel::default(),
+            aborted: HashSet::new(),
+            aborted_check: None,
+            previews: HashMap::new(),
+            preview_formats: Default::default(),
+            live: None,
+            search: TopicSearch::new(),
+            produce: TopicProduce::new(),
+            bulk: TopicBulk::new(),
+            replay: TopicReplay::new(),
+            partition_key: "".to_string(),
         }
     }
 
@@ -172,7 +398,12 @@ impl TopicInfo {
     }
 
     pub fn set_topic(&mut self, topic: &KafkaTreeTopic) -> &mut Self {
+        let mut changed = false;
         if &self.topic != topic {
+            changed = true;
+            self.live = None;
+            self.page = None;
+            self.search.reset();
             self.topic = topic.clone();
             self.partition_len = topic.partitions.len() as i32;
             self.topic_pref = TopicPref::from_db(
@@ -185,6 +416,9 @@ impl TopicInfo {
             info!("{}", &topic.server_name);
             match KafkaServer::from_db(&self.db, &self.topic.server_name) {
                 Ok(server) => {
+                    self.isolation = server.isolation_level;
+                    self.decoders = create_decoders(&server);
+                    changed = true;
                     self.server = server;
                     // fill topic ops
                     self.ops = TopicOps::from_server(&self.server).ok();
@@ -198,16 +432,100 @@ impl TopicInfo {
             }
         }
 
+        if changed {
+            self.bind_protobuf();
+            self.produce.set_formats(
+                &self.topic_pref.key_format,
+                &self.topic_pref.data_format,
+                &self.encoders,
+            );
+            self.bulk
+                .set_formats(&self.topic_pref.key_format, &self.topic_pref.data_format);
+            self.replay
+                .set_topic(&self.server, KafkaServer::all(&self.db));
+        }
         self
     }
 
+    /// Partition the server partitioner chooses for the key of the calculator.
+    /// Key is converted by the encoder of the topic key format
+    fn key_partition(&self) -> Result<String, String> {
+        let key = if self.partition_key.is_empty() {
+            None
+        } else {
+            match self.encoders.get(&self.topic_pref.key_format) {
+                Some(encoder) => Some(
+                    encoder
+                        .encode(&self.partition_key)
+                        .map_err(|e| e.to_string())?,
+                ),
+                None => {
+                    return Err(format!(
+                        "{} keys can't be encoded",
+                        self.topic_pref.key_format
+                    ))
+                }
+            }
+        };
+        let partitioner = self.server.partitioner;
+        let partitions = self.topic.partitions.len() as i32;
+        Ok(match partitioner.partition(key.as_deref(), partitions) {
+            Some(partition) => format!("partition {} ({})", partition, partitioner),
+            None => format!("random partition ({})", partitioner),
+        })
+    }
+
+    /// Loads proto file of the topic and registers Protobuf decoder and encoder for
+    /// the selected message type. Codecs of the previous topic are removed
+    fn bind_protobuf(&mut self) {
+        self.clear_previews();
+        self.decoders.remove(ProtobufDecoder::NAME);
+        self.encoders.remove(ProtobufDecoder::NAME);
+        self.proto_messages = Ok(vec![]);
+        if !self.topic_pref.proto_file.is_empty() {
+            self.load_protobuf();
+        }
+        self.produce.validate(&self.encoders);
+    }
+
+    fn load_protobuf(&mut self) {
+        match load_descriptors(&self.topic_pref.proto_file) {
+            Ok(pool) => {
+                self.proto_messages = Ok(message_names(&pool));
+                if !self.topic_pref.proto_message.is_empty() {
+                    match ProtobufDecoder::from_pool(&pool, &self.topic_pref.proto_message) {
+                        Ok(decoder) => {
+                            self.decoders.register(Box::new(decoder));
+                            if let Some(message) =
+                                pool.get_message_by_name(&self.topic_pref.proto_message)
+                            {
+                                self.encoders
+                                    .register(Box::new(ProtobufEncoder::new(message)));
+                            }
+                        }
+                        Err(err) => error!("Error creating protobuf decoder: {}", err),
+                    }
+                }
+            }
+            Err(err) => self.proto_messages = Err(err.to_string()),
+        }
+    }
+
     pub fn ui(&mut self, ui: &mut Ui) {
+        self.check_formats();
         ui.horizontal(|ui| {
-            [InfoPanel::Properties, InfoPanel::Data]
-                .into_iter()
-                .for_each(|s| {
-                    ui.selectable_value(&mut self.selected_panel, s, s.to_string());
-                })
+            [
+                InfoPanel::Properties,
+                InfoPanel::Data,
+                InfoPanel::Produce,
+                InfoPanel::Bulk,
+                InfoPanel::Replay,
+                InfoPanel::Search,
+            ]
+            .into_iter()
+            .for_each(|s| {
+                ui.selectable_value(&mut self.selected_panel, s, s.to_string());
+            })
         });
         ui.separator();
 
@@ -216,6 +534,24 @@ impl TopicInfo {
             |ui| match self.selected_panel {
                 InfoPanel::Properties => self.show_properties(ui, false),
                 InfoPanel::Data => self.show_data(ui),
+                InfoPanel::Produce => self.produce.ui(
+                    ui,
+                    &self.encoders,
+                    self.ops.as_ref().map(|ops| &ops.producer),
+                    &self.topic,
+                ),
+                InfoPanel::Bulk => self.bulk.ui(ui, &self.encoders, &self.server, &self.topic),
+                InfoPanel::Replay => self.replay.ui(ui, &self.server, &self.topic),
+                InfoPanel::Search => self.search.ui(
+                    ui,
+                    &self.server,
+                    &self.topic,
+                    self.decoders.get(&self.topic_pref.key_format),
+                    &Decompressing::new(
+                        self.topic_pref.data_compression,
+                        self.decoders.get(&self.topic_pref.data_format),
+                    ),
+                ),
             },
         );
     }
@@ -269,16 +605,13 @@ impl TopicInfo {
                     egui::ComboBox::from_id_source("key")
                         .selected_text(&self.topic_pref.key_format)
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(
-                                &mut self.topic_pref.key_format,
-                                "Binary".to_string(),
-                                "Binary".to_string(),
-                            );
-                            ui.selectable_value(
-                                &mut self.topic_pref.key_format,
-                                "String".to_string(),
-                                "String".to_string(),
-                            );
+                            for name in self.decoders.names() {
+                                ui.selectable_value(
+                                    &mut self.topic_pref.key_format,
+                                    name.to_string(),
+                                    name,
+                                );
+                            }
                         });
                     ui.end_row();
 
@@ -286,96 +619,492 @@ impl TopicInfo {
                     egui::ComboBox::from_id_source("data")
                         .selected_text(&self.topic_pref.data_format)
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(
-                                &mut self.topic_pref.data_format,
-                                "Binary".to_string(),
-                                "Binary".to_string(),
-                            );
-                            ui.selectable_value(
-                                &mut self.topic_pref.data_format,
-                                "String".to_string(),
-                                "String".to_string(),
-                            );
+                            for name in self.decoders.names() {
+                                ui.selectable_value(
+                                    &mut self.topic_pref.data_format,
+                                    name.to_string(),
+                                    name,
+                                );
+                            }
                         });
                     ui.end_row();
 
+                    ui.label("Compression");
+                    egui::ComboBox::from_id_source("data_compression")
+                        .selected_text(self.topic_pref.data_compression.to_string())
+                        .show_ui(ui, |ui| {
+                            for compression in Compression::ALL {
+                                ui.selectable_value(
+                                    &mut self.topic_pref.data_compression,
+                                    compression,
+                                    compression.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Payload is decompressed before decoding");
+                    ui.end_row();
+
+                    ui.label("Proto file");
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.topic_pref.proto_file)
+                            .on_hover_text(".proto file or compiled FileDescriptorSet");
+                        if ui.button("Load").clicked() {
+                            self.bind_protobuf();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Proto message");
+                    let proto_message = self.topic_pref.proto_message.clone();
+                    match &self.proto_messages {
+                        Ok(names) => {
+                            egui::ComboBox::from_id_source("proto_message")
+                                .selected_text(&self.topic_pref.proto_message)
+                                .show_ui(ui, |ui| {
+                                    for name in names {
+                                        ui.selectable_value(
+                                            &mut self.topic_pref.proto_message,
+                                            name.clone(),
+                                            name,
+                                        );
+                                    }
+                                });
+                        }
+                        Err(err) => {
+                            ui.colored_label(Color32::RED, err);
+                        }
+                    }
+                    // Protobuf format is offered as soon as the message type is selected
+                    if proto_message != self.topic_pref.proto_message {
+                        self.bind_protobuf();
+                    }
+                    ui.end_row();
+
                     ui.label("Max messages");
                     ui.add(egui::DragValue::new(&mut self.topic_pref.last_messages));
                     ui.end_row();
+
+                    ui.label("Key partition")
+                        .on_hover_text("Partition of messages produced with the key");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.partition_key).hint_text("key"),
+                        );
+                        match self.key_partition() {
+                            Ok(text) => ui.label(text),
+                            Err(err) => ui.colored_label(Color32::RED, err),
+                        };
+                    });
+                    ui.end_row();
                     ui.separator();
                     ui.end_row();
 
                     if ui.button("Update").clicked() {
                         self.topic_pref.store(&self.db_topics);
+                        self.bind_protobuf();
                     }
                     ui.end_row();
                 }
             });
     }
 
-    fn read_data(&mut self) -> AnyResult<Vec<OwnedMessage>> {
+    /// Reads data from the selected partition or from all partitions of the topic
+    /// when no partition is selected. In the last case messages are merged into one
+    /// timeline, partitions which failed are skipped and returned as errors. Error is
+    /// returned when all partitions fail
+    fn read_partitions<F>(&self, read: F) -> AnyResult<(Vec<OwnedMessage>, Vec<String>)>
+    where
+        F: Fn(i32) -> AnyResult<Vec<OwnedMessage>>,
+    {
+        if self.partition != -1 {
+            return read(self.partition).map(|data| (data, vec![]));
+        }
+
+        let mut parts = vec![];
+        let mut errors = vec![];
+        for p in &self.topic.partitions {
+            match read(p.id) {
+                Ok(data) => parts.push(data),
+                Err(err) => {
+                    error!("Error reading partition {}: {}", p.id, err);
+                    errors.push(format!("partition {}: {}", p.id, err));
+                }
+            }
+        }
+        if parts.is_empty() && !errors.is_empty() {
+            return Err(anyhow!(errors.join("; ")));
+        }
+        Ok((merge_by_timestamp(parts), errors))
+    }
+
+    /// Reads messages selected by the offset type. Errors of skipped partitions are
+    /// returned with the messages
+    fn read_data(&mut self) -> AnyResult<(Vec<OwnedMessage>, Vec<String>)> {
         if let Some(ops) = &self.ops {
+            let consumer = ops.reader(self.isolation);
             let timeout = time::Duration::from_secs(1);
-            let data = match self.offset_type {
-                MessagesOffset::FromTail => {
-                    if self.partition == -1 {
-                        // read from all partitions
-                        self.topic.partitions.iter().fold(vec![], |mut v, p| {
-                            match ops.consumer.read_from_partition_tail(
-                                &self.topic.name,
-                                p.id,
-                                self.topic_pref.last_messages as i64,
-                                timeout,
-                            ) {
-                                Ok(data) => {
-                                    v.extend(data);
-                                    v
-                                }
-                                Err(_) => v,
-                            }
-                        })
-                    } else {
-                        ops.consumer.read_from_partition_tail(
-                            &self.topic.name,
-                            self.partition,
-                            self.topic_pref.last_messages as i64,
-                            timeout,
-                        )?
-                    }
+            let max_messages = self.topic_pref.last_messages as i64;
+            let topic = &self.topic.name;
+            match self.offset_type {
+                MessagesOffset::FromTail => self.read_partitions(|p| {
+                    consumer.read_from_partition_tail(topic, p, max_messages, timeout)
+                }),
+                MessagesOffset::FromStart => self.read_partitions(|p| {
+                    consumer.read_from_partition(topic, p, max_messages, timeout)
+                }),
+                MessagesOffset::FromOffset => self.read_partitions(|p| {
+                    consumer.read_from_partition_offset(
+                        topic,
+                        p,
+                        self.offset,
+                        max_messages,
+                        timeout,
+                    )
+                }),
+                MessagesOffset::FromTime => match self.timestamp.timestamp_millis() {
+                    Some(ts) => self.read_partitions(|p| {
+                        consumer.read_from_timestamp(topic, p, ts, max_messages, timeout)
+                    }),
+                    None => Err(anyhow!("invalid date")),
+                },
+                // Offsets of different partitions are not related
+                MessagesOffset::FromRange if self.partition == -1 => {
+                    Err(anyhow!("select partition to read offsets range"))
                 }
-                MessagesOffset::FromStart => {
-                    vec![]
+                MessagesOffset::FromRange => {
+                    let (low, high) = consumer.read_watermarks(topic, self.partition)?;
+                    let start = self.offset.clamp(low, high);
+                    let end = self.range_end.clamp(start, high);
+                    let read = consumer.read_range(
+                        topic,
+                        self.partition,
+                        start,
+                        end,
+                        max_messages as usize,
+                        timeout,
+                    )?;
+                    let errors = if read.stalled {
+                        let stall = KafkaOpsError::ReadStalled(read.position, self.partition);
+                        vec![stall.to_string()]
+                    } else {
+                        vec![]
+                    };
+                    Ok((read.messages, errors))
                 }
-                MessagesOffset::FromOffset => {
-                    vec![]
+            }
+        } else {
+            Ok((vec![], vec![]))
+        }
+    }
+
+    /// Shows offsets window of the data read from the selected partition.
+    /// Paging is not available when all partitions are shown
+    fn update_page(&mut self) {
+        self.page = None;
+        if self.partition == -1 {
+            return;
+        }
+        if let Some(ops) = &self.ops {
+            match ops
+                .consumer
+                .read_watermarks(&self.topic.name, self.partition)
+            {
+                Ok((low, high)) => {
+                    let start = self.data.first().map(|m| m.offset()).unwrap_or(low);
+                    let end = self.data.last().map(|m| m.offset() + 1).unwrap_or(start);
+                    self.page = Some(PageWindow {
+                        start,
+                        end,
+                        low,
+                        high,
+                    });
                 }
+                Err(err) => error!("Error reading partition {}: {}", self.partition, err),
+            }
+        }
+    }
+
+    /// Reads page of `last_messages` messages of the selected partition
+    fn read_page(&mut self, page_move: PageMove) -> AnyResult<()> {
+        let ops = match &self.ops {
+            Some(ops) => ops,
+            None => return Ok(()),
+        };
+        let topic = &self.topic.name;
+        let page_size = cmp::max(1, self.topic_pref.last_messages as i64);
+        self.selected_data = 0;
+        let (low, high) = ops.consumer.read_watermarks(topic, self.partition)?;
+        if low == high {
+            self.data.clear();
+        } else {
+            let start = match (page_move, &self.page) {
+                (PageMove::First, _) => low,
+                (PageMove::Last, _) => high - page_size,
+                (PageMove::Previous, Some(page)) => page.start - page_size,
+                (PageMove::Next, Some(page)) => page.end,
+                (PageMove::Offset(offset), _) => offset,
+                (_, None) => low,
             };
+            // Offsets may be removed by retention since the last read
+            let start = cmp::min(cmp::max(start, low), high - 1);
+            self.data = ops.reader(self.isolation).read_from_partition_offset(
+                topic,
+                self.partition,
+                start,
+                page_size,
+                time::Duration::from_secs(1),
+            )?;
+        }
+        self.update_page();
+        self.mark_aborted();
+        Ok(())
+    }
 
-            Ok(data)
+    /// Starts background search of aborted transactions messages in the data list. All
+    /// messages are committed when data is read with read_committed isolation level
+    fn mark_aborted(&mut self) {
+        self.clear_aborted();
+        if self.isolation != IsolationLevel::ReadUncommitted || self.data.is_empty() {
+            return;
+        }
+        let mut offsets: HashMap<i32, Vec<i64>> = HashMap::new();
+        for msg in &self.data {
+            offsets
+                .entry(msg.partition())
+                .or_default()
+                .push(msg.offset());
+        }
+        if let Some(ops) = &self.ops {
+            let (results, receiver) = mpsc::channel();
+            let check = AbortedCheck {
+                topic: self.topic.name.clone(),
+                offsets,
+                timeout: time::Duration::from_secs(2),
+                results,
+            };
+            match ops.aborted.send(check) {
+                Ok(()) => self.aborted_check = Some(receiver),
+                Err(err) => error!("Error checking transactions: {}", err),
+            }
+        }
+    }
+
+    /// Stops search of aborted transactions and forgets the found messages
+    fn clear_aborted(&mut self) {
+        self.aborted.clear();
+        self.aborted_check = None;
+    }
+
+    /// Moves aborted messages found by the background search into the aborted set
+    fn receive_aborted(&mut self) {
+        if let Some(check) = &self.aborted_check {
+            loop {
+                match check.try_recv() {
+                    Ok((partition, Ok(aborted))) => self
+                        .aborted
+                        .extend(aborted.into_iter().map(|offset| (partition, offset))),
+                    Ok((partition, Err(err))) => {
+                        error!("Error checking transactions of {}: {}", partition, err)
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.aborted_check = None;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns available offsets range for the selected partition or for
+    /// all partitions of the topic
+    fn watermarks(&self) -> Option<(i64, i64)> {
+        let ops = self.ops.as_ref()?;
+        if self.partition == -1 {
+            self.topic
+                .partitions
+                .iter()
+                .filter_map(|p| ops.consumer.read_watermarks(&self.topic.name, p.id).ok())
+                .reduce(|(s, e), (ps, pe)| (cmp::min(s, ps), cmp::max(e, pe)))
         } else {
-            Ok(vec![])
+            ops.consumer
+                .read_watermarks(&self.topic.name, self.partition)
+                .ok()
+        }
+    }
+
+    /// Starts background consumer reading new messages from the selected partitions
+    fn start_live(&mut self) -> AnyResult<()> {
+        let partitions = if self.partition == -1 {
+            self.topic.partitions.iter().map(|p| p.id).collect()
+        } else {
+            vec![self.partition]
+        };
+
+        let server = KafkaServer {
+            isolation_level: self.isolation,
+            ..self.server.clone()
+        };
+        let (commands, messages) = server.open()?.create_consumer()?.start_worker(
+            &self.topic.name,
+            &partitions,
+            LIVE_BUFFER_SIZE,
+        )?;
+
+        self.data.clear();
+        self.selected_data = 0;
+        self.live = Some(LiveTail {
+            commands,
+            messages,
+            paused: false,
+        });
+        Ok(())
+    }
+
+    /// Moves received messages into data list keeping no more than `last_messages` of them
+    fn receive_live(&mut self) {
+        if let Some(live) = &self.live {
+            self.data.extend(live.messages.try_iter());
+            let max_messages = self.topic_pref.last_messages as usize;
+            if self.data.len() > max_messages {
+                let removed = self.data.len() - max_messages;
+                self.data.drain(..removed);
+                // Selection follows the message, it is lost when the message is removed
+                self.selected_data = self.selected_data.saturating_sub(removed);
+            }
+        }
+    }
+
+    /// Drops decoded previews of data rows and search results when the topic formats change
+    fn check_formats(&mut self) {
+        let formats = (
+            self.topic_pref.key_format.clone(),
+            self.topic_pref.data_format.clone(),
+            self.topic_pref.data_compression,
+        );
+        if formats != self.preview_formats {
+            self.clear_previews();
+            self.preview_formats = formats;
+        }
+    }
+
+    fn clear_previews(&mut self) {
+        self.previews.clear();
+        self.search.clear_previews();
+    }
+
+    /// Decodes previews of new data rows. Previews of rows no longer in the data list
+    /// are removed
+    fn update_previews(&mut self) {
+        if self.previews.len() > self.data.len() {
+            let rows = self
+                .data
+                .iter()
+                .map(|msg| (msg.partition(), msg.offset()))
+                .collect::<HashSet<(i32, i64)>>();
+            self.previews.retain(|row, _| rows.contains(row));
+        }
+
+        let key_decoder = self.decoders.get(&self.topic_pref.key_format);
+        let payload_decoder = Decompressing::new(
+            self.topic_pref.data_compression,
+            self.decoders.get(&self.topic_pref.data_format),
+        );
+        for msg in &self.data {
+            let row = (msg.partition(), msg.offset());
+            // Rows are decoded again when their schemas are loaded
+            if self.previews.get(&row).is_none_or(RowPreview::is_loading) {
+                let preview = RowPreview::new(msg, key_decoder, &payload_decoder);
+                self.previews.insert(row, preview);
+            }
         }
     }
 
     pub fn show_data(&mut self, ui: &mut Ui) {
+        self.receive_live();
+        self.receive_aborted();
+        self.update_previews();
+        if matches!(&self.live, Some(live) if !live.paused)
+            || self.aborted_check.is_some()
+            || self.previews.values().any(RowPreview::is_loading)
+        {
+            ui.ctx().request_repaint();
+        }
+
         ui.horizontal(|ui| {
-            if ui.button("refresh").clicked() {
-                match self.read_data() {
-                    Ok(data) => {
-                        self.data = data;
+            match &mut self.live {
+                None => {
+                    if ui.button("refresh").clicked() {
+                        match self.read_data() {
+                            Ok((data, errors)) => {
+                                self.data = data;
+                                self.selected_data = 0;
+                                self.read_errors = errors;
+                                self.update_page();
+                                self.mark_aborted();
+                            }
+                            Err(err) => {
+                                self.data.clear();
+                                self.selected_data = 0;
+                                self.read_errors = vec![err.to_string()];
+                                self.page = None;
+                                self.clear_aborted();
+                                error!("Error reading data: {}", err)
+                            }
+                        }
                     }
-                    Err(err) => {
-                        self.data.clear();
-                        error!("Error reading data: {}", err)
+
+                    if ui
+                        .button("live")
+                        .on_hover_text("Continuously read new messages")
+                        .clicked()
+                    {
+                        self.page = None;
+                        self.read_errors.clear();
+                        self.clear_aborted();
+                        if let Err(err) = self.start_live() {
+                            error!("Error starting live tail: {}", err);
+                        }
+                    }
+                    if self.aborted_check.is_some() {
+                        ui.label("checking transactions...");
+                    }
+                }
+                Some(live) => {
+                    if ui.button("stop").clicked() {
+                        self.live = None;
+                    } else {
+                        if ui
+                            .button(if live.paused { "resume" } else { "pause" })
+                            .clicked()
+                        {
+                            live.toggle_pause();
+                        }
+                        if ui.button("clear").clicked() {
+                            self.data.clear();
+                            self.selected_data = 0;
+                        }
+                        ui.label(format!("{} messages", self.data.len()));
                     }
                 }
             }
 
             ui.separator();
             ui.label("filter");
-            ui.text_edit_singleline(&mut self.filter);
+            if ui
+                .text_edit_singleline(&mut self.filter)
+                .on_hover_text(FILTER_HELP)
+                .changed()
+            {
+                self.message_filter = MessageFilter::parse(&self.filter).map_err(|e| e.to_string());
+            }
+            if let Err(err) = &self.message_filter {
+                ui.colored_label(Color32::RED, err);
+            }
 
             ui.label("partition");
+            let partition = self.partition;
             egui::ComboBox::from_id_source("partitions")
                 .selected_text(if self.partition >= 0 {
                     self.partition.to_string()
@@ -396,6 +1125,29 @@ impl TopicInfo {
                         );
                     }
                 });
+            if partition != self.partition {
+                self.live = None;
+                self.page = None;
+            }
+
+            ui.label("isolation");
+            let isolation = self.isolation;
+            egui::ComboBox::from_id_source("isolation_level")
+                .selected_text(self.isolation.to_string())
+                .show_ui(ui, |ui| {
+                    [
+                        IsolationLevel::ReadUncommitted,
+                        IsolationLevel::ReadCommitted,
+                    ]
+                    .into_iter()
+                    .for_each(|level| {
+                        ui.selectable_value(&mut self.isolation, level, level.to_string());
+                    });
+                });
+            if isolation != self.isolation {
+                self.live = None;
+                self.clear_aborted();
+            }
 
             ui.label("offset");
             egui::ComboBox::from_id_source("offset_type")
@@ -411,29 +1163,56 @@ impl TopicInfo {
                         MessagesOffset::FromStart,
                         MessagesOffset::FromStart.to_string(),
                     );
-                    if self.partition != -1 {
-                        ui.selectable_value(
-                            &mut self.offset_type,
-                            MessagesOffset::FromOffset,
-                            MessagesOffset::FromOffset.to_string(),
-                        );
-                    }
+                    ui.selectable_value(
+                        &mut self.offset_type,
+                        MessagesOffset::FromOffset,
+                        MessagesOffset::FromOffset.to_string(),
+                    );
+                    ui.selectable_value(
+                        &mut self.offset_type,
+                        MessagesOffset::FromTime,
+                        MessagesOffset::FromTime.to_string(),
+                    );
+                    ui.selectable_value(
+                        &mut self.offset_type,
+                        MessagesOffset::FromRange,
+                        MessagesOffset::FromRange.to_string(),
+                    );
                 });
 
             if self.offset_type == MessagesOffset::FromOffset {
                 // Read offset
-                if let Some(ops) = &self.ops {
-                    let (s, e) = ops
-                        .consumer
-                        .read_watermarks(&self.topic.name, self.partition)
-                        .ok()
-                        .unwrap_or((0, 0));
-                    ui.label(format!("Range: {} - {}: ", s, e));
-                }
-                ui.add(egui::DragValue::new(&mut self.offset));
+                let (s, e) = self.watermarks().unwrap_or((0, 0));
+                ui.label(format!("Range: {} - {}: ", s, e));
+                ui.add(egui::DragValue::new(&mut self.offset).clamp_range(s..=cmp::max(s, e - 1)));
+            }
+
+            if self.offset_type == MessagesOffset::FromTime {
+                self.timestamp.ui(ui);
+            }
+
+            if self.offset_type == MessagesOffset::FromRange && self.partition == -1 {
+                ui.colored_label(Color32::RED, "select partition");
+            } else if self.offset_type == MessagesOffset::FromRange {
+                let (s, e) = self.watermarks().unwrap_or((0, 0));
+                ui.label(format!("Range: {} - {}: ", s, e));
+                ui.add(egui::DragValue::new(&mut self.offset).clamp_range(s..=e));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut self.range_end).clamp_range(self.offset..=e))
+                    .on_hover_text("End offset, exclusive. No more than Max messages are read");
             }
         });
 
+        if self.partition != -1 && self.live.is_none() {
+            self.show_pager(ui);
+        }
+        if !self.read_errors.is_empty() {
+            ui.colored_label(
+                Color32::RED,
+                format!("Not read: {}", self.read_errors.join("; ")),
+            );
+        }
+
         ui.vertical_centered_justified(|ui| {
             egui::TopBottomPanel::top("data_list")
                 .resizable(true)
@@ -445,15 +1224,50 @@ impl TopicInfo {
                             .min_col_width(80.0)
                             .max_col_width(600.0)
                             .show(ui, |ui| {
+                                ui.strong("#");
+                                ui.strong("Partition");
+                                ui.strong("Offset");
+                                ui.strong("Timestamp");
+                                ui.strong("Key");
+                                ui.strong("Payload");
+                                ui.end_row();
+
+                                // Gaps are not shown between messages hidden by filter
+                                let filtered =
+                                    matches!(&self.message_filter, Ok(f) if !f.is_empty());
                                 let mut index = 0;
+                                let mut last_offsets = HashMap::new();
                                 for d in &self.data {
                                     index += 1;
+                                    // Offsets are skipped by control records of transactions,
+                                    // compaction and aborted messages in read_committed mode
+                                    if let Some(last) = last_offsets
+                                        .insert(d.partition(), d.offset())
+                                        .filter(|_| !filtered)
+                                    {
+                                        if d.offset() > last + 1 {
+                                            Self::draw_gap_row(
+                                                ui,
+                                                d.partition(),
+                                                last + 1,
+                                                d.offset(),
+                                            );
+                                        }
+                                    }
+                                    let preview = match Self::shown_preview(
+                                        &self.previews,
+                                        &self.message_filter,
+                                        d,
+                                    ) {
+                                        Some(preview) => preview,
+                                        None => continue,
+                                    };
                                     ui.selectable_value(
                                         &mut self.selected_data,
                                         index,
                                         index.to_string(),
                                     );
-                                    self.draw_data_row(ui, &d);
+                                    self.draw_data_row(ui, d, preview);
                                 }
                             });
                     });
@@ -463,54 +1277,147 @@ impl TopicInfo {
                 egui::ScrollArea::vertical()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
-                        if self.selected_data != 0 && self.selected_data <= self.data.len() {
-                            if self.topic_pref.data_format == "String" {
-                                let s = self.data[self.selected_data - 1]
-                                    .payload()
-                                    .and_then(|m| from_utf8(m).ok())
-                                    .unwrap_or("");
-                                let mut text = s.to_string();
-                                ui.add(
-                                    egui::TextEdit::multiline(&mut text)
-                                        // .font(egui::TextStyle::Monospace) // for cursor height
-                                        .code_editor()
-                                        .desired_rows(10)
-                                        .lock_focus(true)
-                                        .desired_width(f32::INFINITY),
-                                );
-                            }
+                        // Selected row may be hidden by the filter
+                        let selected = self
+                            .selected_data
+                            .checked_sub(1)
+                            .and_then(|index| self.data.get(index))
+                            .filter(|d| {
+                                Self::shown_preview(&self.previews, &self.message_filter, d)
+                                    .is_some()
+                            });
+                        if let Some(selected) = selected {
+                            self.detail.ui(
+                                ui,
+                                selected,
+                                self.decoders.get(&self.topic_pref.key_format),
+                                &Decompressing::new(
+                                    self.topic_pref.data_compression,
+                                    self.decoders.get(&self.topic_pref.data_format),
+                                ),
+                            );
                         }
                     });
             });
         });
     }
 
-    fn draw_data_row(&self, ui: &mut Ui, msg: &OwnedMessage) {
-        ui.label(msg.partition().to_string());
-        if self.topic_pref.key_format == "String" {
-            let s = msg.key().and_then(|m| from_utf8(m).ok()).unwrap_or("");
-            ui.label(s);
-        } else {
-            ui.label("[hex]...");
+    /// Draws page navigation buttons and the current offsets window
+    fn show_pager(&mut self, ui: &mut Ui) {
+        let mut page_move = None;
+        ui.horizontal(|ui| {
+            let (has_previous, has_next) = match &self.page {
+                Some(page) => (page.start > page.low, page.end < page.high),
+                None => (false, false),
+            };
+            if ui.button("|<").on_hover_text("First page").clicked() {
+                page_move = Some(PageMove::First);
+            }
+            if ui
+                .add_enabled(has_previous, egui::Button::new("<"))
+                .on_hover_text("Previous page")
+                .clicked()
+            {
+                page_move = Some(PageMove::Previous);
+            }
+            if ui
+                .add_enabled(has_next, egui::Button::new(">"))
+                .on_hover_text("Next page")
+                .clicked()
+            {
+                page_move = Some(PageMove::Next);
+            }
+            if ui.button(">|").on_hover_text("Last page").clicked() {
+                page_move = Some(PageMove::Last);
+            }
+
+            ui.separator();
+            ui.add(egui::DragValue::new(&mut self.jump_offset).clamp_range(0..=i64::MAX));
+            if ui.button("go to offset").clicked() {
+                page_move = Some(PageMove::Offset(self.jump_offset));
+            }
+
+            ui.separator();
+            if let Some(page) = &self.page {
+                if page.start < page.end {
+                    ui.label(format!(
+                        "Offsets {} - {} of {} - {}",
+                        page.start,
+                        page.end - 1,
+                        page.low,
+                        page.high
+                    ));
+                } else {
+                    ui.label(format!("No messages, range {} - {}", page.low, page.high));
+                }
+            }
+        });
+
+        if let Some(page_move) = page_move {
+            match self.read_page(page_move) {
+                Ok(_) => self.read_errors.clear(),
+                Err(err) => {
+                    self.data.clear();
+                    self.read_errors = vec![err.to_string()];
+                    self.page = None;
+                    error!("Error reading data: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Returns preview of the data row if the row is shown. Rows not decoded yet and rows
+    /// not matching the message filter are hidden
+    fn shown_preview<'a>(
+        previews: &'a HashMap<(i32, i64), RowPreview>,
+        message_filter: &Result<MessageFilter, String>,
+        msg: &OwnedMessage,
+    ) -> Option<&'a RowPreview> {
+        let preview = previews.get(&(msg.partition(), msg.offset()))?;
+        match message_filter {
+            // Paths are evaluated on the payload decoded by the topic format
+            Ok(filter) if !filter.matches_with(msg, |_| preview.json.clone()) => None,
+            _ => Some(preview),
         }
+    }
 
-        if self.topic_pref.data_format == "String" {
-            let s = msg.payload().and_then(|m| from_utf8(m).ok()).unwrap_or("");
-            let s = if s.len() > 100 {
-                s.replace("\n", " ")[..100].to_string()
-            } else {
-                s.to_string()
-            };
-            ui.label(s);
+    /// Draws row for offsets `[start, end)` missing in the partition
+    fn draw_gap_row(ui: &mut Ui, partition: i32, start: i64, end: i64) {
+        ui.label("");
+        ui.label(partition.to_string());
+        if end - start > 1 {
+            ui.colored_label(Color32::GRAY, format!("{} - {}", start, end - 1));
         } else {
-            ui.label("[hex]...");
+            ui.colored_label(Color32::GRAY, start.to_string());
         }
+        ui.label("");
+        ui.label("");
+        ui.colored_label(
+            Color32::GRAY,
+            format!(
+                "{} offsets skipped: transaction markers, aborted or removed messages",
+                end - start
+            ),
+        );
+        ui.end_row();
+    }
 
-        // ui.label(msg.headers().map(|hdr|
-        //     for i in 0..hdr.count() {
-        //         let h = hdr.get(i);
-        //     }
-        // ));
+    fn draw_data_row(&self, ui: &mut Ui, msg: &OwnedMessage, preview: &RowPreview) {
+        ui.label(msg.partition().to_string());
+        if self.aborted.contains(&(msg.partition(), msg.offset())) {
+            ui.colored_label(Color32::RED, msg.offset().to_string())
+                .on_hover_text("Message of aborted transaction");
+        } else {
+            ui.label(msg.offset().to_string());
+        }
+        ui.label(
+            msg.timestamp()
+                .to_millis()
+                .map(format_timestamp)
+                .unwrap_or_default(),
+        );
+        preview.key.ui(ui);
+        preview.payload.ui(ui);
 
         ui.end_row();
     }
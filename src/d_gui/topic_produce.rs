@@ -0,0 +1,346 @@
+use crate::codec::{EncoderRegistry, ProtobufDecoder};
+use crate::d_gui::message_detail::highlight_json;
+use crate::d_gui::model::KafkaTreeTopic;
+use crate::kafka::{KafkaOpsError, PendingDelivery, ProducerOps};
+use eframe::egui;
+use eframe::egui::{Color32, TextStyle, Ui};
+use std::time::{Duration, Instant};
+
+/// Formats entered as JSON text, payload of these formats is highlighted
+const JSON_FORMATS: [&str; 5] = ["JSON", "MessagePack", "CBOR", "BSON", ProtobufDecoder::NAME];
+
+/// How long the flush button waits for queued messages
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Encodes text with the encoder of the format
+fn encode(encoders: &EncoderRegistry, format: &str, text: &str) -> Result<Vec<u8>, String> {
+    match encoders.get(format) {
+        Some(encoder) => encoder.encode(text).map_err(|e| e.to_string()),
+        None => Err(format!("{} messages can't be produced", format)),
+    }
+}
+
+/// Panel sending single messages to the topic. Key and payload are entered as text
+/// and converted by the encoders of the selected formats
+#[derive(Debug)]
+pub struct TopicProduce {
+    key: String,
+    key_format: String,
+    key_data: Result<Vec<u8>, String>,
+    payload: String,
+    payload_format: String,
+    payload_data: Result<Vec<u8>, String>,
+    headers: Vec<(String, String)>,
+    /// Partition chosen by the partitioner when `None`
+    partition: Option<i32>,
+    pending: Option<PendingDelivery>,
+    /// Time the flush button stops waiting for queued messages
+    flush_deadline: Option<Instant>,
+    status: Result<String, String>,
+}
+
+impl TopicProduce {
+    pub fn new() -> Self {
+        Self {
+            key: "".to_string(),
+            key_format: "String".to_string(),
+            key_data: Ok(vec![]),
+            payload: "".to_string(),
+            payload_format: "String".to_string(),
+            payload_data: Ok(vec![]),
+            headers: vec![],
+            partition: None,
+            pending: None,
+            flush_deadline: None,
+            status: Ok("".to_string()),
+        }
+    }
+
+    /// Selects key and payload formats of the topic preferences and validates entered text
+    pub fn set_formats(
+        &mut self,
+        key_format: &str,
+        payload_format: &str,
+        encoders: &EncoderRegistry,
+    ) {
+        self.key_format = key_format.to_string();
+        self.payload_format = payload_format.to_string();
+        self.partition = None;
+        self.pending = None;
+        self.flush_deadline = None;
+        self.status = Ok("".to_string());
+        self.validate(encoders);
+    }
+
+    /// Encodes key and payload, errors are shown under the fields
+    pub fn validate(&mut self, encoders: &EncoderRegistry) {
+        self.key_data = if self.key.is_empty() {
+            Ok(vec![])
+        } else {
+            encode(encoders, &self.key_format, &self.key)
+        };
+        self.payload_data = encode(encoders, &self.payload_format, &self.payload);
+    }
+
+    fn send(&mut self, producer: &ProducerOps, topic: &str) -> Result<String, String> {
+        let (key, payload) = match (&self.key_data, &self.payload_data) {
+            (Ok(key), Ok(payload)) => (key, payload),
+            (Err(err), _) | (_, Err(err)) => return Err(err.clone()),
+        };
+        let headers = self
+            .headers
+            .iter()
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let key = if self.key.is_empty() { None } else { Some(key) };
+
+        let pending = producer
+            .send(topic, self.partition, key, Some(payload), headers, None)
+            .map_err(|e| e.to_string())?;
+        let status = format!("Sending message of {} bytes...", payload.len());
+        self.pending = Some(pending);
+        Ok(status)
+    }
+
+    /// Shows the delivery report of the last sent message when it is received
+    fn poll_delivery(&mut self, ui: &Ui) {
+        let result = match &self.pending {
+            Some(pending) => pending.try_result(),
+            None => return,
+        };
+        match result {
+            Some(Ok(delivery)) => {
+                self.status = Ok(format!(
+                    "Delivered to partition {} at offset {}",
+                    delivery.partition, delivery.offset
+                ));
+                self.pending = None;
+            }
+            Some(Err(err)) => {
+                self.status = Err(format!("Delivery failed: {}", err));
+                self.pending = None;
+            }
+            None => ui.ctx().request_repaint(),
+        }
+    }
+
+    /// Checks queued messages while the flush button waits for them. Producer delivers
+    /// messages in its background thread, so the GUI thread is not blocked
+    fn poll_flush(&mut self, ui: &Ui, producer: Option<&ProducerOps>) {
+        let (deadline, producer) = match (self.flush_deadline, producer) {
+            (Some(deadline), Some(producer)) => (deadline, producer),
+            _ => {
+                self.flush_deadline = None;
+                return;
+            }
+        };
+        match producer.in_flight_count() {
+            0 => {
+                self.status = Ok("All messages delivered".to_string());
+                self.flush_deadline = None;
+            }
+            count if Instant::now() >= deadline => {
+                self.status = Err(KafkaOpsError::FlushTimeout(count).to_string());
+                self.flush_deadline = None;
+            }
+            count => {
+                self.status = Ok(format!("Waiting for {} messages...", count));
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        encoders: &EncoderRegistry,
+        producer: Option<&ProducerOps>,
+        topic: &KafkaTreeTopic,
+    ) {
+        self.poll_delivery(ui);
+        self.poll_flush(ui, producer);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            let ready = producer.is_some()
+                && self.pending.is_none()
+                && self.key_data.is_ok()
+                && self.payload_data.is_ok();
+            if ui.add_enabled(ready, egui::Button::new("send")).clicked() {
+                if let Some(producer) = producer {
+                    self.status = self.send(producer, &topic.name);
+                }
+            }
+            if producer.is_some()
+                && ui
+                    .add_enabled(self.flush_deadline.is_none(), egui::Button::new("flush"))
+                    .on_hover_text("Waits until queued messages are delivered")
+                    .clicked()
+            {
+                self.flush_deadline = Some(Instant::now() + FLUSH_TIMEOUT);
+                ui.ctx().request_repaint();
+            }
+
+            ui.label("partition");
+            egui::ComboBox::from_id_source("produce_partition")
+                .selected_text(partition_text(self.partition))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.partition, None, partition_text(None));
+                    for p in &topic.partitions {
+                        ui.selectable_value(
+                            &mut self.partition,
+                            Some(p.id),
+                            partition_text(Some(p.id)),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("The partitioner of the server chooses the partition on auto");
+        });
+
+        match &self.status {
+            Ok(text) => ui.label(text),
+            Err(text) => ui.colored_label(Color32::RED, text),
+        };
+        ui.separator();
+
+        egui::Grid::new("produce_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Key");
+                ui.horizontal(|ui| {
+                    changed |=
+                        format_combo(ui, "produce_key_format", &mut self.key_format, encoders);
+                    changed |= ui
+                        .text_edit_singleline(&mut self.key)
+                        .on_hover_text("Message without key is sent when empty")
+                        .changed();
+                });
+                ui.end_row();
+
+                if let Err(err) = &self.key_data {
+                    ui.label("");
+                    ui.colored_label(Color32::RED, err);
+                    ui.end_row();
+                }
+
+                ui.label("Headers");
+                ui.vertical(|ui| {
+                    let mut removed = None;
+                    for (i, (name, value)) in self.headers.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(name)
+                                    .id_source(("produce_header_name", i))
+                                    .hint_text("name")
+                                    .desired_width(150.0),
+                            );
+                            ui.add(
+                                egui::TextEdit::singleline(value)
+                                    .id_source(("produce_header_value", i))
+                                    .hint_text("value"),
+                            );
+                            if ui.button("x").clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = removed {
+                        self.headers.remove(i);
+                    }
+                    if ui.button("add header").clicked() {
+                        self.headers.push(("".to_string(), "".to_string()));
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Payload");
+                changed |= format_combo(
+                    ui,
+                    "produce_payload_format",
+                    &mut self.payload_format,
+                    encoders,
+                );
+                ui.end_row();
+            });
+
+        let json = JSON_FORMATS.contains(&self.payload_format.as_str());
+        if json {
+            ui.horizontal(|ui| {
+                if ui.button("format").clicked() {
+                    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&self.payload) {
+                        self.payload = serde_json::to_string_pretty(&value).unwrap_or_default();
+                        changed = true;
+                    }
+                }
+            });
+        }
+        match &self.payload_data {
+            Ok(data) => ui.label(format!("{} bytes", data.len())),
+            Err(err) => ui.colored_label(Color32::RED, err),
+        };
+
+        egui::ScrollArea::vertical()
+            .id_source("produce_payload")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+                    let font_id = TextStyle::Monospace.resolve(ui.style());
+                    let mut job = if json {
+                        highlight_json(text, font_id)
+                    } else {
+                        egui::text::LayoutJob::simple(
+                            text.to_string(),
+                            font_id,
+                            ui.visuals().text_color(),
+                            wrap_width,
+                        )
+                    };
+                    job.wrap_width = wrap_width;
+                    ui.fonts().layout_job(job)
+                };
+                changed |= ui
+                    .add(
+                        egui::TextEdit::multiline(&mut self.payload)
+                            .id_source("produce_payload_text")
+                            .code_editor()
+                            .desired_rows(12)
+                            .desired_width(f32::INFINITY)
+                            .layouter(&mut layouter),
+                    )
+                    .changed();
+            });
+
+        if changed {
+            self.validate(encoders);
+        }
+    }
+}
+
+fn partition_text(partition: Option<i32>) -> String {
+    match partition {
+        Some(partition) => partition.to_string(),
+        None => "auto".to_string(),
+    }
+}
+
+/// Combo box of formats with encoders, returns true when the format is changed
+pub fn format_combo(
+    ui: &mut Ui,
+    id: &str,
+    format: &mut String,
+    encoders: &EncoderRegistry,
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_source(id)
+        .selected_text(format.as_str())
+        .show_ui(ui, |ui| {
+            for name in encoders.names() {
+                changed |= ui
+                    .selectable_value(format, name.to_string(), name)
+                    .changed();
+            }
+        });
+    changed
+}
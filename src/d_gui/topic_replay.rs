@@ -0,0 +1,327 @@
+use crate::d_gui::date_time::DateTimeEdit;
+use crate::d_gui::model::KafkaTreeTopic;
+use crate::d_gui::topic_bulk::BulkRun;
+use crate::kafka::{
+    start_replay, HeaderRule, KafkaServer, ReplayRange, ReplaySource, ReplayTransform,
+};
+use anyhow::{anyhow, Result as AnyResult};
+use eframe::egui;
+use eframe::egui::Ui;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum RangeKind {
+    Offsets,
+    Time,
+}
+
+impl Display for RangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeKind::Offsets => f.write_str("Offsets"),
+            RangeKind::Time => f.write_str("Time"),
+        }
+    }
+}
+
+/// Header rule as edited in the panel
+#[derive(Debug, Clone)]
+struct HeaderRow {
+    remove: bool,
+    name: String,
+    value: String,
+}
+
+/// Panel copying messages of the topic to a topic of the same or another server
+#[derive(Debug)]
+pub struct TopicReplay {
+    /// Source partition, all partitions when `None`
+    partition: Option<i32>,
+    range_kind: RangeKind,
+    start_offset: i64,
+    end_offset: i64,
+    /// Reads offsets up to the end of partitions
+    to_end: bool,
+    from_time: DateTimeEdit,
+    to_time: DateTimeEdit,
+    servers: Vec<KafkaServer>,
+    target_server: String,
+    target_topic: String,
+    /// Copying into the source topic itself is confirmed
+    into_source: bool,
+    keep_partition: bool,
+    keep_timestamp: bool,
+    headers: Vec<HeaderRow>,
+    /// Messages per second, 0 is unlimited
+    rate: u32,
+    run: BulkRun,
+}
+
+impl TopicReplay {
+    pub fn new() -> Self {
+        Self {
+            partition: None,
+            range_kind: RangeKind::Offsets,
+            start_offset: 0,
+            end_offset: 0,
+            to_end: true,
+            from_time: DateTimeEdit::now(),
+            to_time: DateTimeEdit::now(),
+            servers: vec![],
+            target_server: "".to_string(),
+            target_topic: "{topic}-copy".to_string(),
+            into_source: false,
+            keep_partition: false,
+            keep_timestamp: true,
+            headers: vec![],
+            rate: 0,
+            run: BulkRun::new(),
+        }
+    }
+
+    /// Cancels running copy and selects the server of the topic as the target
+    pub fn set_topic(&mut self, server: &KafkaServer, servers: Vec<KafkaServer>) {
+        self.run.reset();
+        self.partition = None;
+        self.servers = servers;
+        self.target_server = server.name.clone();
+        self.into_source = false;
+    }
+
+    /// Returns `true` when the target is the source topic on the same server
+    fn targets_source(&self, server: &KafkaServer, topic: &KafkaTreeTopic) -> bool {
+        self.target_server == server.name
+            && self.transform().target_topic(&topic.name) == topic.name
+    }
+
+    fn source(&self, topic: &KafkaTreeTopic) -> AnyResult<ReplaySource> {
+        let range = match self.range_kind {
+            RangeKind::Offsets => {
+                let end = if self.to_end {
+                    i64::MAX
+                } else {
+                    self.end_offset
+                };
+                ReplayRange::Offsets(self.start_offset, end)
+            }
+            RangeKind::Time => match (
+                self.from_time.timestamp_millis(),
+                self.to_time.timestamp_millis(),
+            ) {
+                (Some(from), Some(to)) => ReplayRange::Time(from, to),
+                _ => return Err(anyhow!("invalid date")),
+            },
+        };
+        let partitions = match self.partition {
+            Some(partition) => vec![partition],
+            None => topic.partitions.iter().map(|p| p.id).collect(),
+        };
+        Ok(ReplaySource {
+            topic: topic.name.clone(),
+            partitions,
+            range,
+        })
+    }
+
+    fn transform(&self) -> ReplayTransform {
+        ReplayTransform {
+            topic: self.target_topic.clone(),
+            keep_partition: self.keep_partition,
+            keep_timestamp: self.keep_timestamp,
+            headers: self
+                .headers
+                .iter()
+                .filter(|row| !row.name.is_empty())
+                .map(|row| {
+                    if row.remove {
+                        HeaderRule::Remove(row.name.clone())
+                    } else {
+                        HeaderRule::Set(row.name.clone(), row.value.clone())
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn start(&mut self, server: &KafkaServer, topic: &KafkaTreeTopic) {
+        self.run.reset();
+        let source = self.source(topic);
+        let transform = self.transform();
+        let target = self.servers.iter().find(|s| s.name == self.target_server);
+        let rate = self.rate;
+        self.into_source = false;
+        self.run.start("Reading...", || {
+            let target = target.ok_or_else(|| anyhow!("target server is not selected"))?;
+            Ok(start_replay(
+                server.open()?.create_consumer()?,
+                target.open()?.create_producer()?,
+                source?,
+                transform,
+                rate,
+            ))
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, server: &KafkaServer, topic: &KafkaTreeTopic) {
+        ui.horizontal(|ui| {
+            let targets_source = self.targets_source(server, topic);
+            let ready = !self.target_topic.is_empty() && (!targets_source || self.into_source);
+            if self.run.buttons_ui(ui, ready) {
+                self.start(server, topic);
+            }
+            if targets_source && !self.run.is_running() {
+                ui.checkbox(&mut self.into_source, "copy into the source topic")
+                    .on_hover_text("Target server and topic are the same as the source ones");
+            }
+
+            ui.label("messages/sec");
+            ui.add(egui::DragValue::new(&mut self.rate).clamp_range(0..=1000000))
+                .on_hover_text("0 sends without limit");
+        });
+
+        self.run.status_ui(ui);
+        ui.separator();
+
+        ui.add_enabled_ui(!self.run.is_running(), |ui| {
+            egui::Grid::new("replay_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .show(ui, |ui| {
+                    self.source_ui(ui, topic);
+                    self.target_ui(ui);
+                });
+        });
+
+        self.run.progress_ui(ui, "replay_errors");
+    }
+
+    fn source_ui(&mut self, ui: &mut Ui, topic: &KafkaTreeTopic) {
+        ui.label("Partition");
+        let partition_text = |partition: Option<i32>| match partition {
+            Some(partition) => partition.to_string(),
+            None => "all".to_string(),
+        };
+        egui::ComboBox::from_id_source("replay_partition")
+            .selected_text(partition_text(self.partition))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.partition, None, partition_text(None));
+                for p in &topic.partitions {
+                    ui.selectable_value(
+                        &mut self.partition,
+                        Some(p.id),
+                        partition_text(Some(p.id)),
+                    );
+                }
+            });
+        ui.end_row();
+
+        ui.label("Range");
+        egui::ComboBox::from_id_source("replay_range")
+            .selected_text(self.range_kind.to_string())
+            .show_ui(ui, |ui| {
+                for kind in [RangeKind::Offsets, RangeKind::Time] {
+                    ui.selectable_value(&mut self.range_kind, kind, kind.to_string());
+                }
+            });
+        ui.end_row();
+
+        match self.range_kind {
+            RangeKind::Offsets => {
+                ui.label("From offset");
+                ui.add(egui::DragValue::new(&mut self.start_offset).clamp_range(0..=i64::MAX));
+                ui.end_row();
+
+                ui.label("To offset");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.to_end, "end");
+                    if !self.to_end {
+                        ui.add(
+                            egui::DragValue::new(&mut self.end_offset)
+                                .clamp_range(self.start_offset..=i64::MAX),
+                        )
+                        .on_hover_text("The message at this offset is not copied");
+                    }
+                });
+                ui.end_row();
+            }
+            RangeKind::Time => {
+                ui.label("From");
+                ui.horizontal(|ui| self.from_time.ui(ui));
+                ui.end_row();
+
+                ui.label("To");
+                ui.horizontal(|ui| self.to_time.ui(ui));
+                ui.end_row();
+            }
+        }
+    }
+
+    fn target_ui(&mut self, ui: &mut Ui) {
+        ui.separator();
+        ui.end_row();
+
+        ui.label("Target server");
+        egui::ComboBox::from_id_source("replay_server")
+            .selected_text(&self.target_server)
+            .show_ui(ui, |ui| {
+                for server in &self.servers {
+                    ui.selectable_value(&mut self.target_server, server.name.clone(), &server.name);
+                }
+            });
+        ui.end_row();
+
+        ui.label("Target topic");
+        ui.text_edit_singleline(&mut self.target_topic)
+            .on_hover_text("{topic} is replaced by the source topic name");
+        ui.end_row();
+
+        ui.label("Keep");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.keep_timestamp, "timestamp");
+            ui.checkbox(&mut self.keep_partition, "partition")
+                .on_hover_text("Otherwise the partitioner of the target server is used");
+        });
+        ui.end_row();
+
+        ui.label("Headers");
+        ui.vertical(|ui| {
+            let mut removed = None;
+            for (i, row) in self.headers.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut row.remove, "remove");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut row.name)
+                            .id_source(("replay_header_name", i))
+                            .hint_text("name")
+                            .desired_width(150.0),
+                    );
+                    if !row.remove {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut row.value)
+                                .id_source(("replay_header_value", i))
+                                .hint_text("value"),
+                        );
+                    }
+                    if ui.button("x").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                self.headers.remove(i);
+            }
+            if ui
+                .button("add rule")
+                .on_hover_text("Sets or removes the header of copied messages")
+                .clicked()
+            {
+                self.headers.push(HeaderRow {
+                    remove: false,
+                    name: "".to_string(),
+                    value: "".to_string(),
+                });
+            }
+        });
+        ui.end_row();
+    }
+}
@@ -0,0 +1,301 @@
+use crate::codec::MessageDecoder;
+use crate::d_gui::date_time::{format_timestamp, DateTimeEdit};
+use crate::d_gui::message_detail::{MessageDetail, Preview};
+use crate::d_gui::model::KafkaTreeTopic;
+use crate::kafka::{
+    ConsumerCommand, KafkaServer, MessageFilter, SearchEvent, SearchProgress, SearchStart,
+};
+use anyhow::{anyhow, Result as AnyResult};
+use eframe::egui;
+use eframe::egui::{Color32, Ui};
+use log::error;
+use rdkafka::message::OwnedMessage;
+use rdkafka::Message;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::mpsc::{Receiver, Sender};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum StartKind {
+    Beginning,
+    Timestamp,
+    Offset,
+}
+
+impl Display for StartKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartKind::Beginning => f.write_str("Beginning"),
+            StartKind::Timestamp => f.write_str("Time"),
+            StartKind::Offset => f.write_str("Offset"),
+        }
+    }
+}
+
+/// Channels of the background search worker. Worker stops when this structure is dropped
+#[derive(Debug)]
+struct SearchJob {
+    commands: Sender<ConsumerCommand>,
+    events: Receiver<SearchEvent>,
+}
+
+impl Drop for SearchJob {
+    fn drop(&mut self) {
+        let _ = self.commands.send(ConsumerCommand::Stop);
+    }
+}
+
+/// Search of messages through all partitions of the topic
+#[derive(Debug)]
+pub struct TopicSearch {
+    expression: String,
+    start_kind: StartKind,
+    offset: i64,
+    timestamp: DateTimeEdit,
+    max_results: usize,
+    job: Option<SearchJob>,
+    progress: BTreeMap<i32, SearchProgress>,
+    results: Vec<OwnedMessage>,
+    /// Decoded keys and payloads of the results
+    previews: Vec<(Preview, Preview)>,
+    /// Index of the result shown in the detail pane
+    selected: Option<usize>,
+    detail: MessageDetail,
+    status: Result<String, String>,
+}
+
+impl TopicSearch {
+    pub fn new() -> Self {
+        Self {
+            expression: "".to_string(),
+            start_kind: StartKind::Beginning,
+            offset: 0,
+            timestamp: DateTimeEdit::now(),
+            max_results: 1000,
+            job: None,
+            progress: BTreeMap::new(),
+            results: vec![],
+            previews: vec![],
+            selected: None,
+            detail: MessageDetail::new(),
+            status: Ok("".to_string()),
+        }
+    }
+
+    /// Cancels running search and clears results
+    pub fn reset(&mut self) {
+        self.job = None;
+        self.progress.clear();
+        self.results.clear();
+        self.previews.clear();
+        self.selected = None;
+        self.status = Ok("".to_string());
+    }
+
+    /// Drops decoded previews, results are decoded again with the current topic formats
+    pub fn clear_previews(&mut self) {
+        self.previews.clear();
+    }
+
+    fn start(&mut self, server: &KafkaServer, topic: &KafkaTreeTopic) -> AnyResult<()> {
+        let filter = MessageFilter::parse(&self.expression)?;
+        let start = match self.start_kind {
+            StartKind::Beginning => SearchStart::Beginning,
+            StartKind::Offset => SearchStart::Offset(self.offset),
+            StartKind::Timestamp => match self.timestamp.timestamp_millis() {
+                Some(ts) => SearchStart::Timestamp(ts),
+                None => return Err(anyhow!("invalid date")),
+            },
+        };
+        let partitions = topic.partitions.iter().map(|p| p.id).collect::<Vec<i32>>();
+
+        let (commands, events) = server.open()?.create_consumer()?.start_search(
+            &topic.name,
+            &partitions,
+            start,
+            filter,
+            self.max_results,
+        )?;
+
+        self.reset();
+        self.job = Some(SearchJob { commands, events });
+        Ok(())
+    }
+
+    fn receive(&mut self) {
+        if let Some(job) = &self.job {
+            let mut finished = false;
+            for event in job.events.try_iter() {
+                match event {
+                    SearchEvent::Progress(progress) => {
+                        self.progress.insert(progress.partition, progress);
+                    }
+                    SearchEvent::Found(msg) => self.results.push(msg),
+                    SearchEvent::Error(err) => error!("Error searching messages: {}", err),
+                    SearchEvent::Finished => finished = true,
+                }
+            }
+
+            if finished {
+                self.job = None;
+                self.status = Ok(format!("Finished, {} messages found", self.results.len()));
+            }
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        server: &KafkaServer,
+        topic: &KafkaTreeTopic,
+        key_decoder: &dyn MessageDecoder,
+        payload_decoder: &dyn MessageDecoder,
+    ) {
+        self.receive();
+        let loading = |(key, payload): &(Preview, Preview)| {
+            *key == Preview::Loading || *payload == Preview::Loading
+        };
+        // Results are decoded again when their schemas are loaded
+        for (index, msg) in self.results.iter().enumerate() {
+            if matches!(self.previews.get(index), Some(preview) if !loading(preview)) {
+                continue;
+            }
+            let preview = (
+                Preview::new(msg.key(), key_decoder, 48),
+                Preview::new(msg.payload(), payload_decoder, 100),
+            );
+            if index < self.previews.len() {
+                self.previews[index] = preview;
+            } else {
+                self.previews.push(preview);
+            }
+        }
+        if self.job.is_some() || self.previews.iter().any(loading) {
+            ui.ctx().request_repaint();
+        }
+
+        ui.horizontal(|ui| {
+            if self.job.is_none() {
+                if ui.button("search").clicked() {
+                    if let Err(err) = self.start(server, topic) {
+                        self.status = Err(err.to_string());
+                    }
+                }
+            } else if ui.button("cancel").clicked() {
+                self.job = None;
+                self.status = Ok(format!("Cancelled, {} messages found", self.results.len()));
+            }
+
+            ui.label("filter");
+            ui.text_edit_singleline(&mut self.expression)
+                .on_hover_text("Filter expression, the same as in the Data panel");
+
+            ui.label("from");
+            egui::ComboBox::from_id_source("search_start")
+                .selected_text(self.start_kind.to_string())
+                .show_ui(ui, |ui| {
+                    [
+                        StartKind::Beginning,
+                        StartKind::Timestamp,
+                        StartKind::Offset,
+                    ]
+                    .into_iter()
+                    .for_each(|k| {
+                        ui.selectable_value(&mut self.start_kind, k, k.to_string());
+                    });
+                });
+            match self.start_kind {
+                StartKind::Timestamp => self.timestamp.ui(ui),
+                StartKind::Offset => {
+                    ui.add(egui::DragValue::new(&mut self.offset).clamp_range(0..=i64::MAX));
+                }
+                StartKind::Beginning => {}
+            }
+
+            ui.label("max results");
+            ui.add(egui::DragValue::new(&mut self.max_results).clamp_range(1..=100000));
+        });
+
+        match &self.status {
+            Ok(text) => ui.label(text),
+            Err(text) => ui.colored_label(Color32::RED, text),
+        };
+
+        egui::Grid::new("search_progress_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for p in self.progress.values() {
+                    let total = p.end - p.start;
+                    let fraction = if total > 0 {
+                        (p.position - p.start) as f32 / total as f32
+                    } else {
+                        1.0
+                    };
+                    ui.label(format!("partition {}", p.partition));
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .text(format!("{} / {}", p.position, p.end))
+                            .desired_width(300.0),
+                    );
+                    ui.end_row();
+                }
+            });
+        ui.separator();
+
+        ui.vertical_centered_justified(|ui| {
+            egui::TopBottomPanel::top("search_results_panel")
+                .resizable(true)
+                .height_range(200.0..=800.0)
+                .show_inside(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("search_results")
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| self.results_ui(ui));
+                });
+
+            egui::CentralPanel::default().show_inside(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("search_detail")
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        if let Some(msg) = self.selected.and_then(|i| self.results.get(i)) {
+                            self.detail.ui(ui, msg, key_decoder, payload_decoder);
+                        }
+                    });
+            });
+        });
+    }
+
+    fn results_ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("search_results_grid")
+            .striped(true)
+            .min_col_width(60.0)
+            .max_col_width(600.0)
+            .show(ui, |ui| {
+                ui.strong("#");
+                ui.strong("Partition");
+                ui.strong("Offset");
+                ui.strong("Timestamp");
+                ui.strong("Key");
+                ui.strong("Payload");
+                ui.end_row();
+
+                for (i, (msg, (key, payload))) in
+                    self.results.iter().zip(&self.previews).enumerate()
+                {
+                    ui.selectable_value(&mut self.selected, Some(i), (i + 1).to_string());
+                    ui.label(msg.partition().to_string());
+                    ui.label(msg.offset().to_string());
+                    ui.label(
+                        msg.timestamp()
+                            .to_millis()
+                            .map(format_timestamp)
+                            .unwrap_or_default(),
+                    );
+                    key.ui(ui);
+                    payload.ui(ui);
+                    ui.end_row();
+                }
+            });
+    }
+}
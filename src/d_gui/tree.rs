@@ -1,4 +1,7 @@
-use crate::d_gui::model::{KafkaTreeBroker, KafkaTreePartition, KafkaTreeTopic};
+use crate::codec::SchemaRegistry;
+use crate::d_gui::model::{
+    KafkaTreeBroker, KafkaTreeGroup, KafkaTreePartition, KafkaTreeSubject, KafkaTreeTopic,
+};
 use crate::kafka::KafkaServer;
 use anyhow::Result as AnyResult;
 use eframe::egui::{CollapsingHeader, CollapsingResponse, RichText, SelectableLabel, Ui};
@@ -16,7 +19,7 @@ impl Tree {
     pub fn from_servers(servers: &Vec<KafkaServer>) -> Self {
         let subtrees = servers
             .iter()
-            .map(|srv| SubTree::new(TreeNode::Server(srv.clone(), false)))
+            .map(|srv| SubTree::new(TreeNode::Server(Box::new(srv.clone()), false)))
             .collect::<Vec<SubTree>>();
 
         Self {
@@ -59,10 +62,12 @@ impl Tree {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TreeNode {
     Folder(String),
-    Server(KafkaServer, bool),
+    Server(Box<KafkaServer>, bool),
     Broker(KafkaTreeBroker),
     Topic(KafkaTreeTopic),
     Partition(KafkaTreePartition),
+    Group(KafkaTreeGroup),
+    Subject(KafkaTreeSubject),
 }
 
 impl Display for TreeNode {
@@ -73,6 +78,8 @@ impl Display for TreeNode {
             TreeNode::Broker(internal) => f.write_str(&internal.to_string()),
             TreeNode::Topic(internal) => f.write_str(&internal.to_string()),
             TreeNode::Partition(internal) => f.write_str(&internal.to_string()),
+            TreeNode::Group(internal) => f.write_str(&internal.to_string()),
+            TreeNode::Subject(internal) => f.write_str(&internal.to_string()),
         }
     }
 }
@@ -172,6 +179,25 @@ impl SubTree {
                         })
                         .collect::<Vec<SubTree>>();
                     topics.sort_by(|a, b| a.node.to_string().cmp(&b.node.to_string()));
+
+                    // Groups are optional, the user may have no rights to list them
+                    let mut groups = match consumer.read_groups(None) {
+                        Ok(list) => list
+                            .groups()
+                            .iter()
+                            .map(|group| {
+                                SubTree::new(TreeNode::Group(KafkaTreeGroup::from_group(
+                                    server.name.clone(),
+                                    group,
+                                )))
+                            })
+                            .collect::<Vec<SubTree>>(),
+                        Err(err) => {
+                            error!("Error reading consumer groups: {}", err);
+                            vec![]
+                        }
+                    };
+                    groups.sort_by_key(|a| a.node.to_string());
                     if !self.children.is_empty() {
                         self.children.clear();
                     }
@@ -179,8 +205,37 @@ impl SubTree {
                     self.children = vec![
                         SubTree::with_children(TreeNode::Folder("Brokers".to_string()), brokers),
                         SubTree::with_children(TreeNode::Folder("Topics".to_string()), topics),
+                        SubTree::with_children(
+                            TreeNode::Folder("Consumer groups".to_string()),
+                            groups,
+                        ),
                     ];
 
+                    if !server.schema_registry.url.is_empty() {
+                        // Registry is optional, the cluster is usable without it
+                        let subjects = match SchemaRegistry::new(&server.schema_registry)
+                            .and_then(|registry| registry.subjects())
+                        {
+                            Ok(subjects) => subjects
+                                .into_iter()
+                                .map(|name| {
+                                    SubTree::new(TreeNode::Subject(KafkaTreeSubject {
+                                        server_name: server.name.clone(),
+                                        name,
+                                    }))
+                                })
+                                .collect::<Vec<SubTree>>(),
+                            Err(err) => {
+                                error!("Error reading schema registry subjects: {}", err);
+                                vec![]
+                            }
+                        };
+                        self.children.push(SubTree::with_children(
+                            TreeNode::Folder("Schemas".to_string()),
+                            subjects,
+                        ));
+                    }
+
                     self.node = TreeNode::Server(server.clone(), true);
                 }
             }
@@ -238,6 +293,14 @@ impl SubTree {
                 let ps = partition.to_string();
                 self.draw_simple(ui, selected_node, ps);
             }
+            TreeNode::Group(group) => {
+                let gs = group.to_string();
+                self.draw_simple(ui, selected_node, gs);
+            }
+            TreeNode::Subject(subject) => {
+                let ss = subject.to_string();
+                self.draw_simple(ui, selected_node, ss);
+            }
         };
     }
 
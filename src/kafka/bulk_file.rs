@@ -0,0 +1,353 @@
+use chrono::DateTime;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+/// Format of the file with messages for bulk produce
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BulkFormat {
+    /// One JSON object per line with `key`, `value`, `headers`, `partition`
+    /// and `timestamp` fields
+    JsonLines,
+    /// Comma separated values, the first row names the columns
+    Csv,
+}
+
+impl BulkFormat {
+    /// Guesses format by the file extension, JSON lines are used by default
+    pub fn from_path<T: AsRef<str>>(path: T) -> Self {
+        if path.as_ref().to_lowercase().ends_with(".csv") {
+            BulkFormat::Csv
+        } else {
+            BulkFormat::JsonLines
+        }
+    }
+}
+
+impl Display for BulkFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BulkFormat::JsonLines => f.write_str("JSONL"),
+            BulkFormat::Csv => f.write_str("CSV"),
+        }
+    }
+}
+
+/// Message read from the bulk file. Key and value are text converted later
+/// by the encoders of the selected formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkRecord {
+    /// Line of the file where the record starts, counted from 1
+    pub line: usize,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub partition: Option<i32>,
+    /// Milliseconds since the epoch
+    pub timestamp: Option<i64>,
+}
+
+impl BulkRecord {
+    fn new(line: usize) -> Self {
+        Self {
+            line,
+            key: None,
+            value: None,
+            headers: vec![],
+            partition: None,
+            timestamp: None,
+        }
+    }
+}
+
+/// Record of the file which can't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Display for BulkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Parses records of the file content. Invalid records are returned as errors
+/// and don't stop parsing of the rest
+pub fn parse_records(text: &str, format: BulkFormat) -> (Vec<BulkRecord>, Vec<BulkError>) {
+    let results = match format {
+        BulkFormat::JsonLines => parse_json_lines(text),
+        BulkFormat::Csv => parse_csv(text),
+    };
+    let mut records = vec![];
+    let mut errors = vec![];
+    for result in results {
+        match result {
+            Ok(record) => records.push(record),
+            Err(err) => errors.push(err),
+        }
+    }
+    (records, errors)
+}
+
+/// Text of a JSON field, strings are taken as is and other values as JSON
+fn json_text(value: Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s),
+        other => Some(other.to_string()),
+    }
+}
+
+fn parse_partition(text: &str) -> Result<i32, String> {
+    match text.trim().parse::<i32>() {
+        Ok(partition) if partition >= 0 => Ok(partition),
+        _ => Err(format!("invalid partition {}", text)),
+    }
+}
+
+/// Timestamp is either milliseconds since the epoch or RFC 3339 date
+fn parse_timestamp(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    text.parse::<i64>()
+        .or_else(|_| DateTime::parse_from_rfc3339(text).map(|ts| ts.timestamp_millis()))
+        .map_err(|_| format!("invalid timestamp {}", text))
+}
+
+/// Headers are a JSON object with header names as keys
+fn parse_headers(value: Value) -> Result<Vec<(String, String)>, String> {
+    match value {
+        Value::Null => Ok(vec![]),
+        Value::Object(map) => Ok(map
+            .into_iter()
+            .map(|(name, value)| (name, json_text(value).unwrap_or_default()))
+            .collect()),
+        _ => Err("headers must be an object".to_string()),
+    }
+}
+
+fn parse_json_lines(text: &str) -> Vec<Result<BulkRecord, BulkError>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse_json_record(i + 1, line).map_err(|reason| BulkError {
+                line: i + 1,
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn parse_json_record(line: usize, text: &str) -> Result<BulkRecord, String> {
+    let fields = match serde_json::from_str::<Value>(text).map_err(|e| e.to_string())? {
+        Value::Object(fields) => fields,
+        _ => return Err("record must be an object".to_string()),
+    };
+    let mut record = BulkRecord::new(line);
+    for (name, value) in fields {
+        match name.as_str() {
+            "key" => record.key = json_text(value),
+            "value" => record.value = json_text(value),
+            "headers" => record.headers = parse_headers(value)?,
+            "partition" => {
+                record.partition = match json_text(value) {
+                    Some(text) => Some(parse_partition(&text)?),
+                    None => None,
+                }
+            }
+            "timestamp" => {
+                record.timestamp = match json_text(value) {
+                    Some(text) => Some(parse_timestamp(&text)?),
+                    None => None,
+                }
+            }
+            other => return Err(format!("unknown field {}", other)),
+        }
+    }
+    Ok(record)
+}
+
+/// Splits CSV text into rows of cells with the line number of each row.
+/// Quoted cells may contain separators, line breaks and doubled quotes
+fn csv_rows(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            (_, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                cell.push(c);
+            }
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push((row_line, row));
+    }
+    rows.retain(|(_, cells)| cells.iter().any(|c| !c.is_empty()));
+    rows
+}
+
+fn parse_csv(text: &str) -> Vec<Result<BulkRecord, BulkError>> {
+    let mut rows = csv_rows(text).into_iter();
+    let (header_line, columns) = match rows.next() {
+        Some(header) => header,
+        None => return vec![],
+    };
+    let columns = columns
+        .iter()
+        .map(|c| c.trim().to_lowercase())
+        .collect::<Vec<String>>();
+    if let Some(unknown) = columns
+        .iter()
+        .find(|c| !["key", "value", "headers", "partition", "timestamp"].contains(&c.as_str()))
+    {
+        return vec![Err(BulkError {
+            line: header_line,
+            reason: format!("unknown column {}", unknown),
+        })];
+    }
+
+    rows.map(|(line, cells)| {
+        parse_csv_record(line, &columns, cells).map_err(|reason| BulkError { line, reason })
+    })
+    .collect()
+}
+
+/// Empty cells are treated as missing values
+fn parse_csv_record(
+    line: usize,
+    columns: &[String],
+    cells: Vec<String>,
+) -> Result<BulkRecord, String> {
+    if cells.len() > columns.len() {
+        return Err(format!(
+            "{} cells in a row of {} columns",
+            cells.len(),
+            columns.len()
+        ));
+    }
+    let mut record = BulkRecord::new(line);
+    for (column, cell) in columns.iter().zip(cells) {
+        if cell.is_empty() {
+            continue;
+        }
+        match column.as_str() {
+            "key" => record.key = Some(cell),
+            "value" => record.value = Some(cell),
+            "headers" => {
+                let value = serde_json::from_str(&cell).map_err(|e| e.to_string())?;
+                record.headers = parse_headers(value)?;
+            }
+            "partition" => record.partition = Some(parse_partition(&cell)?),
+            _ => record.timestamp = Some(parse_timestamp(&cell)?),
+        }
+    }
+    Ok(record)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kafka::bulk_file::BulkRecord;
+    use crate::kafka::{parse_records, BulkFormat};
+
+    #[test]
+    fn test_json_lines() {
+        let text = r#"{"key": "k1", "value": {"id": 1}, "headers": {"type": "order"}, "partition": 2}
+
+{"value": "text", "timestamp": "2023-11-14T22:13:20Z"}
+{"key": null, "timestamp": 1700000000000}
+[1, 2]
+{"value": "v", "partition": -1}
+{"value": "v", "offset": 5}"#;
+        let (records, errors) = parse_records(text, BulkFormat::JsonLines);
+        assert_eq!(
+            vec![
+                BulkRecord {
+                    line: 1,
+                    key: Some("k1".to_string()),
+                    value: Some(r#"{"id":1}"#.to_string()),
+                    headers: vec![("type".to_string(), "order".to_string())],
+                    partition: Some(2),
+                    timestamp: None,
+                },
+                BulkRecord {
+                    line: 3,
+                    key: None,
+                    value: Some("text".to_string()),
+                    headers: vec![],
+                    partition: None,
+                    timestamp: Some(1700000000000),
+                },
+                BulkRecord {
+                    line: 4,
+                    key: None,
+                    value: None,
+                    headers: vec![],
+                    partition: None,
+                    timestamp: Some(1700000000000),
+                },
+            ],
+            records
+        );
+        assert_eq!(
+            vec![5, 6, 7],
+            errors.iter().map(|e| e.line).collect::<Vec<usize>>()
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let text = "Key,value,headers,partition,timestamp\r\n\
+            k1,\"a, \"\"quoted\"\"\nvalue\",\"{\"\"type\"\":\"\"order\"\"}\",1,1700000000000\r\n\
+            ,v2,,,\n\
+            k3,v3,,x,\n\
+            k4,v4,,,,extra\n";
+        let (records, errors) = parse_records(text, BulkFormat::Csv);
+        assert_eq!(2, records.len());
+        assert_eq!(
+            BulkRecord {
+                line: 2,
+                key: Some("k1".to_string()),
+                value: Some("a, \"quoted\"\nvalue".to_string()),
+                headers: vec![("type".to_string(), "order".to_string())],
+                partition: Some(1),
+                timestamp: Some(1700000000000),
+            },
+            records[0]
+        );
+        assert_eq!(4, records[1].line);
+        assert_eq!(None, records[1].key);
+        assert_eq!(Some("v2".to_string()), records[1].value);
+        assert_eq!(
+            vec![5, 6],
+            errors.iter().map(|e| e.line).collect::<Vec<usize>>()
+        );
+
+        let (records, errors) = parse_records("key,offset\nk,1\n", BulkFormat::Csv);
+        assert!(records.is_empty());
+        assert_eq!("line 1: unknown column offset", errors[0].to_string());
+    }
+}
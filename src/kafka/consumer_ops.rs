@@ -1,16 +1,160 @@
+use crate::kafka::{KafkaOpsError, MessageFilter};
 use anyhow::Result;
-use log::{error, trace};
-use rdkafka::consumer::{BaseConsumer, Consumer};
+use log::{error, info, trace};
+use rdkafka::consumer::{BaseConsumer, CommitMode, Consumer};
+use rdkafka::groups::GroupList;
 use rdkafka::message::OwnedMessage;
 use rdkafka::metadata::Metadata;
-use rdkafka::{Offset, TopicPartitionList};
+use rdkafka::{Message, Offset, TopicPartitionList};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
-use std::{cmp, time};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::{cmp, thread, time};
 
 pub struct ConsumerOps {
     consumer: BaseConsumer,
 }
 
+/// Messages read by [ConsumerOps::read_range]
+#[derive(Debug)]
+pub struct RangeRead {
+    pub messages: Vec<OwnedMessage>,
+    /// Offset where reading stopped. Offsets from it up to the range end are not read
+    pub position: i64,
+    /// Nothing was received at the position for the idle timeout, e.g. because of an
+    /// open transaction
+    pub stalled: bool,
+}
+
+/// Offset committed by consumer group for the topic partition
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartitionLag {
+    pub topic: String,
+    pub partition: i32,
+    pub committed: i64,
+    pub high: i64,
+}
+
+impl PartitionLag {
+    /// Number of messages not yet consumed by the group
+    pub fn lag(&self) -> i64 {
+        cmp::max(self.high - self.committed, 0)
+    }
+}
+
+/// Describes how to compute new committed offset of consumer group
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OffsetReset {
+    Earliest,
+    Latest,
+    /// First offset with timestamp greater than or equal to the given one
+    Timestamp(i64),
+    Offset(i64),
+    /// Shift of the currently committed offset. The beginning of partition is used
+    /// when the group has no committed offset
+    Shift(i64),
+}
+
+/// Planned change of committed offset
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffsetChange {
+    pub topic: String,
+    pub partition: i32,
+    pub current: Option<i64>,
+    pub new: i64,
+}
+
+/// Position in partitions where topic search starts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchStart {
+    Beginning,
+    /// Milliseconds since Unix epoch
+    Timestamp(i64),
+    Offset(i64),
+}
+
+/// Search progress of a partition
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchProgress {
+    pub partition: i32,
+    pub start: i64,
+    /// Next offset to check
+    pub position: i64,
+    /// Offset where search of the partition stops
+    pub end: i64,
+}
+
+impl SearchProgress {
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.end
+    }
+}
+
+/// Events sent by the search worker
+#[derive(Debug)]
+pub enum SearchEvent {
+    Progress(SearchProgress),
+    Found(OwnedMessage),
+    Error(String),
+    /// Search is completed or cancelled
+    Finished,
+}
+
+/// Offsets checked by the worker started with [ConsumerOps::start_aborted_worker]
+#[derive(Debug)]
+pub struct AbortedCheck {
+    pub topic: String,
+    /// Offsets of the messages to check by partition
+    pub offsets: HashMap<i32, Vec<i64>>,
+    pub timeout: time::Duration,
+    /// Receives aborted offsets of every checked partition
+    pub results: Sender<(i32, Result<Vec<i64>>)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ConsumerCommand {
+    Pause,
+    Resume,
+    Stop,
+}
+
+/// Merges messages read from several partitions into one timeline ordered by timestamp.
+/// Offset and partition are used when timestamps are equal. Order of messages inside
+/// each partition is kept. Messages without timestamp go first
+pub fn merge_by_timestamp(parts: Vec<Vec<OwnedMessage>>) -> Vec<OwnedMessage> {
+    let sort_key = |msg: &OwnedMessage| {
+        (
+            msg.timestamp().to_millis().unwrap_or(i64::MIN),
+            msg.offset(),
+            msg.partition(),
+        )
+    };
+
+    let mut result = Vec::with_capacity(parts.iter().map(|p| p.len()).sum());
+    let mut iters = parts
+        .into_iter()
+        .map(|p| p.into_iter().peekable())
+        .collect::<Vec<_>>();
+    let mut heap = iters
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, it)| it.peek().map(|msg| Reverse((sort_key(msg), i))))
+        .collect::<BinaryHeap<_>>();
+
+    while let Some(Reverse((_, i))) = heap.pop() {
+        if let Some(msg) = iters[i].next() {
+            result.push(msg);
+        }
+        if let Some(msg) = iters[i].peek() {
+            heap.push(Reverse((sort_key(msg), i)));
+        }
+    }
+
+    result
+}
+
 impl Debug for ConsumerOps {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_str("ConsumerOps(..)")
@@ -40,6 +184,131 @@ impl ConsumerOps {
             .map_err(|e| e.into())
     }
 
+    /// Reads consumer groups with their members. All groups are returned if group is not specified
+    pub fn read_groups(&self, group: Option<&str>) -> Result<GroupList> {
+        self.consumer
+            .fetch_group_list(group, time::Duration::from_millis(5000))
+            .map_err(|e| e.into())
+    }
+
+    /// Reads offsets committed by the consumer group for all partitions of all topics
+    /// except internal ones and compares them with high watermarks. The consumer must be
+    /// created with `group.id` of the required group. Partitions without committed
+    /// offsets are skipped
+    pub fn read_group_offsets(&self) -> Result<Vec<PartitionLag>> {
+        let md = self.read_metadata()?;
+        let mut tnp = TopicPartitionList::new();
+        md.topics()
+            .iter()
+            .filter(|t| !t.name().starts_with("__"))
+            .for_each(|t| {
+                t.partitions().iter().for_each(|p| {
+                    tnp.add_partition(t.name(), p.id());
+                })
+            });
+
+        let mut offsets = self
+            .read_committed(tnp)?
+            .into_iter()
+            .map(|(topic, partition, committed)| {
+                let (_, high) = self.read_watermarks(&topic, partition)?;
+                Ok(PartitionLag {
+                    topic,
+                    partition,
+                    committed,
+                    high,
+                })
+            })
+            .collect::<Result<Vec<PartitionLag>>>()?;
+
+        offsets.sort_by(|a, b| (&a.topic, a.partition).cmp(&(&b.topic, b.partition)));
+        Ok(offsets)
+    }
+
+    /// Reads committed offsets for the given partitions. Partitions without committed
+    /// offsets are skipped
+    fn read_committed(&self, tnp: TopicPartitionList) -> Result<Vec<(String, i32, i64)>> {
+        let committed = self
+            .consumer
+            .committed_offsets(tnp, time::Duration::from_millis(5000))?;
+
+        Ok(committed
+            .elements()
+            .iter()
+            .filter_map(|elem| match elem.offset() {
+                Offset::Offset(offset) => {
+                    Some((elem.topic().to_string(), elem.partition(), offset))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Computes new committed offsets for the given partitions without changing them.
+    /// New offsets are always inside available partition range
+    /// # Arguments
+    ///   - partitions - list of topic name and partition pairs
+    ///   - reset - the way to compute new offset
+    pub fn plan_group_reset(
+        &self,
+        partitions: &[(String, i32)],
+        reset: &OffsetReset,
+    ) -> Result<Vec<OffsetChange>> {
+        let mut tnp = TopicPartitionList::new();
+        for (topic, partition) in partitions {
+            tnp.add_partition(topic, *partition);
+        }
+
+        let committed = self.read_committed(tnp)?;
+        partitions
+            .iter()
+            .map(|(topic, partition)| {
+                let current = committed
+                    .iter()
+                    .find(|(t, p, _)| t == topic && p == partition)
+                    .map(|(_, _, offset)| *offset);
+                let (low, high) = self.read_watermarks(topic, *partition)?;
+                let target = match reset {
+                    OffsetReset::Earliest => low,
+                    OffsetReset::Latest => high,
+                    OffsetReset::Timestamp(ts) => self
+                        .offset_for_timestamp(topic, *partition, *ts)?
+                        .unwrap_or(high),
+                    OffsetReset::Offset(offset) => *offset,
+                    OffsetReset::Shift(n) => current.unwrap_or(low) + n,
+                };
+
+                Ok(OffsetChange {
+                    topic: topic.clone(),
+                    partition: *partition,
+                    current,
+                    new: target.clamp(low, high),
+                })
+            })
+            .collect()
+    }
+
+    /// Commits new offsets for the consumer group. The consumer must be created with
+    /// `group.id` of the required group. Fails if the group has active members
+    pub fn commit_group_offsets<T: AsRef<str>>(
+        &self,
+        group: T,
+        offsets: &[OffsetChange],
+    ) -> Result<()> {
+        let groups = self.read_groups(Some(group.as_ref()))?;
+        if groups.groups().iter().any(|g| !g.members().is_empty()) {
+            return Err(KafkaOpsError::GroupNotEmpty(group.as_ref().to_string()).into());
+        }
+
+        let mut tnp = TopicPartitionList::new();
+        for change in offsets {
+            tnp.add_partition_offset(&change.topic, change.partition, Offset::Offset(change.new))?;
+        }
+        self.consumer
+            .commit(&tnp, CommitMode::Sync)
+            .map_err(|e| e.into())
+    }
+
     fn read_from_consumer(
         &self,
         n_last_messages: i64,
@@ -97,6 +366,12 @@ impl ConsumerOps {
         max_messages: i64,
         timeout: time::Duration,
     ) -> Result<Vec<OwnedMessage>> {
+        // Check offset against available range
+        let (start, end) = self.read_watermarks(topic.as_ref(), partition)?;
+        if offset < start || offset >= end {
+            return Err(KafkaOpsError::OffsetOutOfRange(offset, start, end).into());
+        }
+
         let mut tnp = TopicPartitionList::new();
         tnp.add_partition_offset(topic.as_ref(), partition, Offset::Offset(offset))?;
         // Assign topic partition
@@ -111,10 +386,417 @@ impl ConsumerOps {
         max_messages: i64,
         timeout: time::Duration,
     ) -> Result<Vec<OwnedMessage>> {
+        // Nothing to wait for when partition is empty
+        let (start, end) = self.read_watermarks(topic.as_ref(), partition)?;
+        if start == end {
+            return Ok(vec![]);
+        }
+
         let mut tnp = TopicPartitionList::new();
         tnp.add_partition_offset(topic.as_ref(), partition, Offset::Beginning)?;
         // Assign topic partition
         self.consumer.assign(&tnp)?;
         self.read_from_consumer(max_messages, timeout)
     }
+
+    /// Reads messages with offsets in range `[start, end)`, no more than `max_messages` of
+    /// them. Reading stops when the end offset is reached, so the result is not truncated
+    /// by slow polls. The end is capped at the high watermark of this consumer, which is
+    /// the last stable offset for read_committed consumers. Reading also stops when nothing
+    /// is received for `idle_timeout`, the messages read before are returned
+    pub fn read_range<T: AsRef<str>>(
+        &self,
+        topic: T,
+        partition: i32,
+        start: i64,
+        end: i64,
+        max_messages: usize,
+        idle_timeout: time::Duration,
+    ) -> Result<RangeRead> {
+        // The watermarks query honors isolation.level of the consumer
+        let (low, high) = self.read_watermarks(topic.as_ref(), partition)?;
+        if start < low || start > high {
+            return Err(KafkaOpsError::OffsetOutOfRange(start, low, high).into());
+        }
+        if end < start {
+            return Err(KafkaOpsError::OffsetOutOfRange(end, start, high).into());
+        }
+        let end = end.min(high);
+        let mut read = RangeRead {
+            messages: vec![],
+            position: start,
+            stalled: false,
+        };
+        if start == end {
+            return Ok(read);
+        }
+
+        let mut tnp = TopicPartitionList::new();
+        tnp.add_partition_offset(topic.as_ref(), partition, Offset::Offset(start))?;
+        self.consumer.assign(&tnp)?;
+
+        let poll_timeout = time::Duration::from_millis(100);
+        let mut last_received = time::Instant::now();
+        while read.position < end && read.messages.len() < max_messages {
+            match self.consumer.poll(poll_timeout) {
+                None => match self.fetch_position(topic.as_ref(), partition) {
+                    Some(offset) if offset > read.position => {
+                        read.position = offset.min(end);
+                        last_received = time::Instant::now();
+                    }
+                    _ => {
+                        if time::Instant::elapsed(&last_received) >= idle_timeout {
+                            read.stalled = true;
+                            break;
+                        }
+                    }
+                },
+                Some(Err(e)) => error!("Error while receiving message {:?}", e),
+                Some(Ok(msg)) => {
+                    last_received = time::Instant::now();
+                    if msg.offset() < end {
+                        read.position = msg.offset() + 1;
+                        read.messages.push(msg.detach());
+                    } else {
+                        read.position = end;
+                    }
+                }
+            }
+        }
+
+        Ok(read)
+    }
+
+    /// Returns fetch position of the assigned partition. Fetch position skips control
+    /// records, compacted messages and, for read_committed consumers, aborted messages
+    fn fetch_position(&self, topic: &str, partition: i32) -> Option<i64> {
+        let positions = self.consumer.position().ok()?;
+        match positions.find_partition(topic, partition)?.offset() {
+            Offset::Offset(offset) => Some(offset),
+            _ => None,
+        }
+    }
+
+    /// Finds which of the given offsets belong to aborted transactions. The consumer must
+    /// use read_committed isolation level: offsets skipped by it are aborted messages.
+    /// Offsets of transactions still open when the timeout expires are not returned
+    pub fn find_aborted<T: AsRef<str>>(
+        &self,
+        topic: T,
+        partition: i32,
+        offsets: &[i64],
+        timeout: time::Duration,
+    ) -> Result<Vec<i64>> {
+        let (start, end) = match (offsets.iter().min(), offsets.iter().max()) {
+            (Some(start), Some(end)) => (*start, *end + 1),
+            _ => return Ok(vec![]),
+        };
+
+        let mut tnp = TopicPartitionList::new();
+        tnp.add_partition_offset(topic.as_ref(), partition, Offset::Offset(start))?;
+        self.consumer.assign(&tnp)?;
+
+        let begin = time::Instant::now();
+        let poll_timeout = time::Duration::from_millis(100);
+        let mut delivered = HashSet::new();
+        let mut position = start;
+        while position < end && time::Instant::elapsed(&begin) < timeout {
+            match self.consumer.poll(poll_timeout) {
+                None => {
+                    if let Some(offset) = self.fetch_position(topic.as_ref(), partition) {
+                        position = cmp::max(position, offset);
+                    }
+                }
+                Some(Err(e)) => error!("Error while receiving message {:?}", e),
+                Some(Ok(msg)) => {
+                    delivered.insert(msg.offset());
+                    position = msg.offset() + 1;
+                }
+            }
+        }
+
+        Ok(offsets
+            .iter()
+            .filter(|offset| **offset < position && !delivered.contains(*offset))
+            .copied()
+            .collect())
+    }
+
+    /// Starts background worker running [ConsumerOps::find_aborted] for offsets of each
+    /// partition of the received checks. Worker sends result of every partition and skips
+    /// the rest of a check when its receiver is dropped. Checks queued behind a newer one
+    /// are skipped. Worker stops when the sender is dropped
+    pub fn start_aborted_worker(self) -> Sender<AbortedCheck> {
+        let (tx, rx) = mpsc::channel::<AbortedCheck>();
+        thread::spawn(move || {
+            info!("Start aborted transactions worker");
+            while let Ok(check) = rx.recv() {
+                let check = rx.try_iter().last().unwrap_or(check);
+                for (partition, offsets) in check.offsets {
+                    let aborted =
+                        self.find_aborted(&check.topic, partition, &offsets, check.timeout);
+                    if check.results.send((partition, aborted)).is_err() {
+                        break;
+                    }
+                }
+            }
+            info!("Finish aborted transactions worker");
+        });
+        tx
+    }
+
+    /// Looks up the earliest offset whose timestamp is greater than or equal to the given
+    /// timestamp. Returns `None` if there are no such messages in the partition
+    /// # Arguments
+    ///   - topic - topic name
+    ///   - partition - partition number
+    ///   - timestamp - milliseconds since Unix epoch
+    pub fn offset_for_timestamp<T: AsRef<str>>(
+        &self,
+        topic: T,
+        partition: i32,
+        timestamp: i64,
+    ) -> Result<Option<i64>> {
+        let mut tnp = TopicPartitionList::new();
+        tnp.add_partition_offset(topic.as_ref(), partition, Offset::Offset(timestamp))?;
+        let offsets = self
+            .consumer
+            .offsets_for_times(tnp, time::Duration::from_millis(1000))?;
+
+        match offsets.find_partition(topic.as_ref(), partition) {
+            Some(elem) => {
+                elem.error()?;
+                match elem.offset() {
+                    Offset::Offset(offset) => Ok(Some(offset)),
+                    _ => Ok(None),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Reads messages produced at or after the given timestamp (milliseconds since Unix epoch)
+    pub fn read_from_timestamp<T: AsRef<str>>(
+        &self,
+        topic: T,
+        partition: i32,
+        timestamp: i64,
+        max_messages: i64,
+        timeout: time::Duration,
+    ) -> Result<Vec<OwnedMessage>> {
+        match self.offset_for_timestamp(topic.as_ref(), partition, timestamp)? {
+            Some(offset) => {
+                self.read_from_partition_offset(topic, partition, offset, max_messages, timeout)
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Starts background worker which reads new messages from the end of given partitions
+    /// and sends them into the returned channel. The worker stops on [ConsumerCommand::Stop]
+    /// or when any of the channels is closed
+    /// # Arguments
+    ///   - topic - topic name
+    ///   - partitions - partitions to read
+    ///   - buffer_size - maximum number of messages waiting in the channel
+    pub fn start_worker<T: AsRef<str>>(
+        self,
+        topic: T,
+        partitions: &[i32],
+        buffer_size: usize,
+    ) -> Result<(Sender<ConsumerCommand>, Receiver<OwnedMessage>)> {
+        let mut tnp = TopicPartitionList::new();
+        for partition in partitions {
+            tnp.add_partition_offset(topic.as_ref(), *partition, Offset::End)?;
+        }
+        self.consumer.assign(&tnp)?;
+
+        let (cmd_tx, cmd_rx) = mpsc::channel::<ConsumerCommand>();
+        let (msg_tx, msg_rx) = mpsc::sync_channel::<OwnedMessage>(buffer_size);
+        thread::spawn(move || {
+            info!("Start consumer worker");
+            let poll_timeout = time::Duration::from_millis(100);
+            let mut paused = false;
+            loop {
+                let cmd = if paused {
+                    cmd_rx.recv().map_err(|_| TryRecvError::Disconnected)
+                } else {
+                    cmd_rx.try_recv()
+                };
+
+                match cmd {
+                    Ok(ConsumerCommand::Pause) => paused = true,
+                    Ok(ConsumerCommand::Resume) => paused = false,
+                    Ok(ConsumerCommand::Stop) | Err(TryRecvError::Disconnected) => break,
+                    Err(TryRecvError::Empty) => {}
+                }
+
+                if paused {
+                    continue;
+                }
+
+                let msg = match self.consumer.poll(poll_timeout) {
+                    None => continue,
+                    Some(Err(e)) => {
+                        error!("Error while receiving message {:?}", e);
+                        continue;
+                    }
+                    Some(Ok(msg)) => msg.detach(),
+                };
+
+                if msg_tx.send(msg).is_err() {
+                    break;
+                }
+            }
+
+            info!("Finish consumer worker");
+        });
+
+        Ok((cmd_tx, msg_rx))
+    }
+
+    /// Starts background worker which scans given partitions from the start position up to
+    /// high watermarks read at the start and sends messages matching the filter. Worker sends
+    /// progress of every partition and stops on [ConsumerCommand::Stop], when `max_results`
+    /// messages are found or when all partitions are scanned
+    pub fn start_search<T: AsRef<str>>(
+        self,
+        topic: T,
+        partitions: &[i32],
+        start: SearchStart,
+        filter: MessageFilter,
+        max_results: usize,
+    ) -> Result<(Sender<ConsumerCommand>, Receiver<SearchEvent>)> {
+        let mut ranges = HashMap::new();
+        let mut tnp = TopicPartitionList::new();
+        for partition in partitions {
+            let (low, high) = self.read_watermarks(topic.as_ref(), *partition)?;
+            let position = match start {
+                SearchStart::Beginning => low,
+                SearchStart::Timestamp(ts) => self
+                    .offset_for_timestamp(topic.as_ref(), *partition, ts)?
+                    .unwrap_or(high),
+                SearchStart::Offset(offset) => offset.clamp(low, high),
+            };
+            if position < high {
+                tnp.add_partition_offset(topic.as_ref(), *partition, Offset::Offset(position))?;
+            }
+            ranges.insert(
+                *partition,
+                SearchProgress {
+                    partition: *partition,
+                    start: position,
+                    position,
+                    end: high,
+                },
+            );
+        }
+        self.consumer.assign(&tnp)?;
+
+        let (cmd_tx, cmd_rx) = mpsc::channel::<ConsumerCommand>();
+        let (event_tx, event_rx) = mpsc::channel::<SearchEvent>();
+        thread::spawn(move || {
+            info!("Start search worker");
+            let poll_timeout = time::Duration::from_millis(100);
+            let progress_interval = time::Duration::from_millis(250);
+            let mut last_progress = time::Instant::now();
+            let mut found = 0;
+
+            loop {
+                match cmd_rx.try_recv() {
+                    Ok(ConsumerCommand::Stop) | Err(TryRecvError::Disconnected) => break,
+                    _ => {}
+                }
+
+                match self.consumer.poll(poll_timeout) {
+                    None => {
+                        // Fetch position skips control records and compacted messages,
+                        // use it to detect end of partitions
+                        if let Ok(positions) = self.consumer.position() {
+                            for elem in positions.elements() {
+                                if let (Offset::Offset(offset), Some(range)) =
+                                    (elem.offset(), ranges.get_mut(&elem.partition()))
+                                {
+                                    range.position = cmp::max(range.position, offset);
+                                }
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        let _ = event_tx.send(SearchEvent::Error(e.to_string()));
+                    }
+                    Some(Ok(msg)) => {
+                        if let Some(range) = ranges.get_mut(&msg.partition()) {
+                            if msg.offset() < range.end {
+                                range.position = msg.offset() + 1;
+                                let msg = msg.detach();
+                                if filter.matches(&msg) {
+                                    found += 1;
+                                    if event_tx.send(SearchEvent::Found(msg)).is_err() {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                let finished = found >= max_results || ranges.values().all(|p| p.is_finished());
+                if finished || time::Instant::elapsed(&last_progress) >= progress_interval {
+                    last_progress = time::Instant::now();
+                    for progress in ranges.values() {
+                        let _ = event_tx.send(SearchEvent::Progress(progress.clone()));
+                    }
+                }
+
+                if finished {
+                    break;
+                }
+            }
+
+            let _ = event_tx.send(SearchEvent::Finished);
+            info!("Finish search worker");
+        });
+
+        Ok((cmd_tx, event_rx))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kafka::merge_by_timestamp;
+    use rdkafka::message::{OwnedMessage, Timestamp};
+    use rdkafka::Message;
+
+    fn create_msg(partition: i32, offset: i64, timestamp: i64) -> OwnedMessage {
+        OwnedMessage::new(
+            None,
+            None,
+            "test".to_string(),
+            Timestamp::CreateTime(timestamp),
+            partition,
+            offset,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_merge_by_timestamp() {
+        let merged = merge_by_timestamp(vec![
+            vec![
+                create_msg(0, 10, 100),
+                create_msg(0, 11, 300),
+                create_msg(0, 12, 250),
+            ],
+            vec![],
+            vec![create_msg(2, 5, 100), create_msg(2, 6, 200)],
+        ]);
+
+        let order = merged
+            .iter()
+            .map(|m| (m.partition(), m.offset()))
+            .collect::<Vec<(i32, i64)>>();
+        // Equal timestamps are ordered by offset, partition order is kept for 11 and 12
+        assert_eq!(vec![(2, 5), (0, 10), (2, 6), (0, 11), (0, 12)], order);
+    }
 }
@@ -1,19 +1,48 @@
 use crate::kafka::consumer_ops::ConsumerOps;
-use crate::kafka::{AdminOps, KafkaOpsError, ProducerOps};
+use crate::kafka::{AdminOps, DeliveryContext, KafkaOpsError, Partitioner, ProducerOps};
 use anyhow::Result;
 use rdkafka::admin::AdminClient;
 use rdkafka::client::DefaultClientContext;
 use rdkafka::config::RDKafkaLogLevel;
-use rdkafka::producer::BaseProducer;
+use rdkafka::producer::ThreadedProducer;
 use rdkafka::ClientConfig;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
 const BOOTSTRAP: &str = "bootstrap.servers";
 const CLIENT_ID: &str = "client.id";
 const GROUP_ID: &str = "group.id";
+const ISOLATION_LEVEL: &str = "isolation.level";
+const PARTITIONER: &str = "partitioner";
 
 pub type Pref = HashMap<String, String>;
 
+/// Visibility of transactional messages for consumers
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum IsolationLevel {
+    /// All messages are returned, including aborted and not yet committed ones
+    ReadUncommitted,
+    /// Only messages of committed transactions are returned. Default of librdkafka
+    #[default]
+    ReadCommitted,
+}
+
+impl IsolationLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "read_uncommitted",
+            IsolationLevel::ReadCommitted => "read_committed",
+        }
+    }
+}
+
+impl Display for IsolationLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 pub struct KafkaOps {
     preferences: Pref,
     client_config: Option<ClientConfig>,
@@ -37,7 +66,7 @@ impl KafkaOps {
         match &self.client_config {
             None => Err(KafkaOpsError::ClientNotOpen.into()),
             Some(client) => client
-                .create::<BaseProducer>()
+                .create_with_context::<_, ThreadedProducer<DeliveryContext>>(DeliveryContext)
                 .map(ProducerOps::new)
                 .map_err(|e| e.into()),
         }
@@ -85,6 +114,18 @@ impl KafkaOpsBuilder {
         self
     }
 
+    pub fn with_isolation_level(mut self, level: IsolationLevel) -> Self {
+        self.preferences
+            .insert(ISOLATION_LEVEL.to_string(), level.as_str().to_string());
+        self
+    }
+
+    pub fn with_partitioner(mut self, partitioner: Partitioner) -> Self {
+        self.preferences
+            .insert(PARTITIONER.to_string(), partitioner.as_str().to_string());
+        self
+    }
+
     pub fn with_security_protocol<T: AsRef<str>>(mut self, protocol: T) -> Self {
         self.preferences.insert(
             "security.protocol".to_string(),
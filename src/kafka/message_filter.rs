@@ -0,0 +1,708 @@
+use crate::kafka::KafkaOpsError;
+use anyhow::Result;
+use rdkafka::message::{Headers, OwnedMessage};
+use rdkafka::Message;
+use regex::Regex;
+use serde_json::Value;
+use std::cell::OnceCell;
+
+/// Filter for kafka messages described by a small expression language.
+///
+/// Predicates:
+///   - `key == "k1"`, `key != "k1"`, `key contains "k"`, `key ~ "^k[0-9]+"`, `key`
+///   - `value contains "error"`, `value ~ "id=\d+"` (`payload` is an alias of `value`)
+///   - `header("trace-id")`, `header("type") == "order"`, `header("type") ~ "ord.*"`
+///   - `partition == 1`, `offset >= 100`, `offset in 100..200`, `timestamp > 1650000000000`
+///   - `$.order.id == "A-1"`, `$.items[0].price > 10`, `$.tags contains "new"`, `$.user`
+///
+/// Predicates are combined with `and`, `or`, `not` and parentheses. Empty expression
+/// matches all messages
+#[derive(Debug)]
+pub struct MessageFilter {
+    expr: Option<Expr>,
+}
+
+impl MessageFilter {
+    pub fn parse<T: AsRef<str>>(text: T) -> Result<Self> {
+        let tokens = tokenize(text.as_ref())?;
+        if tokens.is_empty() {
+            return Ok(Self { expr: None });
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Self { expr: Some(expr) }),
+            Some((pos, token)) => Err(syntax_error(*pos, format!("unexpected {:?}", token))),
+        }
+    }
+
+    /// Returns `true` if the filter has no expression and matches all messages
+    pub fn is_empty(&self) -> bool {
+        self.expr.is_none()
+    }
+
+    /// Matches message, JSON path predicates are evaluated on the payload parsed as JSON
+    pub fn matches(&self, msg: &OwnedMessage) -> bool {
+        self.matches_with(msg, |payload| serde_json::from_slice(payload).ok())
+    }
+
+    /// Matches message, JSON path predicates are evaluated on the document returned by
+    /// `decode` for the payload. It is called at most once per message
+    pub fn matches_with<F>(&self, msg: &OwnedMessage, decode: F) -> bool
+    where
+        F: Fn(&[u8]) -> Option<Value>,
+    {
+        match &self.expr {
+            None => true,
+            Some(expr) => expr.eval(msg, &Payload::new(decode)),
+        }
+    }
+}
+
+/// Payload document shared between all predicates of the expression
+struct Payload<F> {
+    decode: F,
+    json: OnceCell<Option<Value>>,
+}
+
+impl<F: Fn(&[u8]) -> Option<Value>> Payload<F> {
+    fn new(decode: F) -> Self {
+        Self {
+            decode,
+            json: OnceCell::new(),
+        }
+    }
+
+    fn get(&self, msg: &OwnedMessage) -> Option<&Value> {
+        self.json
+            .get_or_init(|| msg.payload().and_then(|p| (self.decode)(p)))
+            .as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Name(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Key,
+    Value,
+    Header(String),
+    Partition,
+    Offset,
+    Timestamp,
+    Json(Vec<PathSegment>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+    Matches,
+    In,
+}
+
+#[derive(Debug)]
+enum Operand {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+    Regex(Regex),
+    Range(f64, f64),
+}
+
+#[derive(Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Exists(Field),
+    Compare(Field, Op, Operand),
+}
+
+impl Expr {
+    /// Evaluates expression. Decoded JSON payload is shared between all predicates
+    fn eval<F: Fn(&[u8]) -> Option<Value>>(&self, msg: &OwnedMessage, json: &Payload<F>) -> bool {
+        match self {
+            Expr::And(l, r) => l.eval(msg, json) && r.eval(msg, json),
+            Expr::Or(l, r) => l.eval(msg, json) || r.eval(msg, json),
+            Expr::Not(e) => !e.eval(msg, json),
+            Expr::Exists(field) => match field {
+                Field::Key => msg.key().is_some(),
+                Field::Value => msg.payload().is_some(),
+                Field::Header(name) => header(msg, name).is_some(),
+                Field::Json(path) => json_value(msg, json, path).is_some(),
+                Field::Partition | Field::Offset | Field::Timestamp => true,
+            },
+            Expr::Compare(field, op, operand) => match field {
+                Field::Key => compare_bytes(msg.key(), *op, operand),
+                Field::Value => compare_bytes(msg.payload(), *op, operand),
+                Field::Header(name) => compare_bytes(header(msg, name), *op, operand),
+                Field::Partition => compare_num(msg.partition() as f64, *op, operand),
+                Field::Offset => compare_num(msg.offset() as f64, *op, operand),
+                Field::Timestamp => match msg.timestamp().to_millis() {
+                    Some(ts) => compare_num(ts as f64, *op, operand),
+                    None => false,
+                },
+                Field::Json(path) => match json_value(msg, json, path) {
+                    Some(value) => compare_json(value, *op, operand),
+                    None => *op == Op::Ne,
+                },
+            },
+        }
+    }
+}
+
+fn header<'a>(msg: &'a OwnedMessage, name: &str) -> Option<&'a [u8]> {
+    let headers = msg.headers()?;
+    (0..headers.count())
+        .filter_map(|i| headers.get(i))
+        .find(|(n, _)| *n == name)
+        .map(|(_, v)| v)
+}
+
+fn json_value<'a, F: Fn(&[u8]) -> Option<Value>>(
+    msg: &OwnedMessage,
+    json: &'a Payload<F>,
+    path: &[PathSegment],
+) -> Option<&'a Value> {
+    let root = json.get(msg)?;
+    path.iter().try_fold(root, |value, segment| match segment {
+        PathSegment::Name(name) => value.get(name),
+        PathSegment::Index(index) => value.get(index),
+    })
+}
+
+fn compare_str(value: &str, op: Op, operand: &Operand) -> bool {
+    match (op, operand) {
+        (Op::Eq, Operand::Str(s)) => value == s,
+        (Op::Ne, Operand::Str(s)) => value != s,
+        (Op::Contains, Operand::Str(s)) => value.contains(s.as_str()),
+        (Op::Matches, Operand::Regex(re)) => re.is_match(value),
+        _ => false,
+    }
+}
+
+fn compare_bytes(value: Option<&[u8]>, op: Op, operand: &Operand) -> bool {
+    match value {
+        Some(bytes) => compare_str(&String::from_utf8_lossy(bytes), op, operand),
+        None => op == Op::Ne,
+    }
+}
+
+fn compare_num(value: f64, op: Op, operand: &Operand) -> bool {
+    match (op, operand) {
+        (Op::Eq, Operand::Num(n)) => value == *n,
+        (Op::Ne, Operand::Num(n)) => value != *n,
+        (Op::Gt, Operand::Num(n)) => value > *n,
+        (Op::Ge, Operand::Num(n)) => value >= *n,
+        (Op::Lt, Operand::Num(n)) => value < *n,
+        (Op::Le, Operand::Num(n)) => value <= *n,
+        (Op::In, Operand::Range(from, to)) => value >= *from && value < *to,
+        _ => false,
+    }
+}
+
+fn compare_json(value: &Value, op: Op, operand: &Operand) -> bool {
+    match (value, op, operand) {
+        (Value::String(s), _, _) => compare_str(s, op, operand),
+        (Value::Number(n), _, _) => n.as_f64().is_some_and(|n| compare_num(n, op, operand)),
+        (Value::Bool(b), Op::Eq, Operand::Bool(o)) => b == o,
+        (Value::Bool(b), Op::Ne, Operand::Bool(o)) => b != o,
+        (Value::Null, Op::Eq, Operand::Null) => true,
+        (Value::Null, Op::Ne, Operand::Null) => false,
+        (Value::Array(items), Op::Contains, _) => {
+            items.iter().any(|item| compare_json(item, Op::Eq, operand))
+        }
+        (_, Op::Ne, _) => true,
+        _ => false,
+    }
+}
+
+fn syntax_error<T: AsRef<str>>(pos: usize, message: T) -> anyhow::Error {
+    KafkaOpsError::InvalidFilter(pos, message.as_ref().to_string()).into()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Path(String),
+    LParen,
+    RParen,
+    Range,
+    Op(&'static str),
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>> {
+    let chars = text.char_indices().collect::<Vec<(usize, char)>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push((pos, Token::LParen));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((pos, Token::RParen));
+                i += 1;
+            }
+            '.' if next == Some('.') => {
+                tokens.push((pos, Token::Range));
+                i += 2;
+            }
+            '=' | '!' | '>' | '<' if next == Some('=') => {
+                let op = match c {
+                    '=' => "==",
+                    '!' => "!=",
+                    '>' => ">=",
+                    _ => "<=",
+                };
+                tokens.push((pos, Token::Op(op)));
+                i += 2;
+            }
+            '>' | '<' | '~' => {
+                let op = match c {
+                    '>' => ">",
+                    '<' => "<",
+                    _ => "~",
+                };
+                tokens.push((pos, Token::Op(op)));
+                i += 1;
+            }
+            '"' => {
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error(pos, "unterminated string")),
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => {
+                            match chars.get(i + 1) {
+                                Some((_, '"')) => s.push('"'),
+                                Some((_, '\\')) => s.push('\\'),
+                                Some((_, c)) => {
+                                    // Keep unknown escapes as is, they are used by regular expressions
+                                    s.push('\\');
+                                    s.push(*c);
+                                }
+                                None => return Err(syntax_error(pos, "unterminated string")),
+                            }
+                            i += 2;
+                        }
+                        Some((_, c)) => {
+                            s.push(*c);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push((pos, Token::Str(s)));
+                i += 1;
+            }
+            '$' => {
+                // Brackets are scanned as a unit, quoted names may contain spaces and operators
+                let start = i;
+                let mut bracket = false;
+                let mut quoted = false;
+                while i < chars.len() {
+                    let c = chars[i].1;
+                    if quoted {
+                        match c {
+                            '\\' => i += 1,
+                            '"' => quoted = false,
+                            _ => {}
+                        }
+                    } else if bracket {
+                        match c {
+                            '"' => quoted = true,
+                            ']' => bracket = false,
+                            _ => {}
+                        }
+                    } else if c == '[' {
+                        bracket = true;
+                    } else if c.is_whitespace() || "=!<>~()".contains(c) {
+                        break;
+                    }
+                    i += 1;
+                }
+                if bracket {
+                    return Err(syntax_error(pos, "missing ']' in path"));
+                }
+                let path = chars[start..i].iter().map(|(_, c)| c).collect();
+                tokens.push((pos, Token::Path(path)));
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i].1.is_ascii_digit() {
+                    i += 1;
+                }
+                // Fraction, but not a range
+                if i + 1 < chars.len() && chars[i].1 == '.' && chars[i + 1].1.is_ascii_digit() {
+                    i += 1;
+                    while i < chars.len() && chars[i].1.is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                let s = chars[start..i].iter().map(|(_, c)| c).collect::<String>();
+                let n = s
+                    .parse::<f64>()
+                    .map_err(|_| syntax_error(pos, format!("invalid number {}", s)))?;
+                tokens.push((pos, Token::Num(n)));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                    i += 1;
+                }
+                let ident = chars[start..i].iter().map(|(_, c)| c).collect();
+                tokens.push((pos, Token::Ident(ident)));
+            }
+            c => return Err(syntax_error(pos, format!("unexpected character '{}'", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses JSON path like `$.items[0].name` or `$["field name"]`
+fn parse_path(pos: usize, path: &str) -> Result<Vec<PathSegment>> {
+    let mut segments = vec![];
+    let mut rest = &path[1..];
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('.') {
+            let end = r.find(['.', '[']).unwrap_or(r.len());
+            if end == 0 {
+                return Err(syntax_error(pos, format!("empty field name in {}", path)));
+            }
+            segments.push(PathSegment::Name(r[..end].to_string()));
+            rest = &r[end..];
+        } else if let Some(r) = rest.strip_prefix('[') {
+            let missing = || syntax_error(pos, format!("missing ']' in {}", path));
+            if let Some(quoted) = r.strip_prefix('"') {
+                let (name, len) = parse_quoted(quoted)
+                    .ok_or_else(|| syntax_error(pos, format!("unterminated name in {}", path)))?;
+                segments.push(PathSegment::Name(name));
+                rest = quoted[len..].strip_prefix(']').ok_or_else(missing)?;
+            } else {
+                let end = r.find(']').ok_or_else(missing)?;
+                let inner = &r[..end];
+                segments.push(PathSegment::Index(inner.parse().map_err(|_| {
+                    syntax_error(pos, format!("invalid index {} in {}", inner, path))
+                })?));
+                rest = &r[end + 1..];
+            }
+        } else {
+            return Err(syntax_error(pos, format!("invalid path {}", path)));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Reads quoted name up to the closing quote, `\"` and `\\` are escapes.
+/// Returns the name and the length of the text including the closing quote
+fn parse_quoted(text: &str) -> Option<(String, usize)> {
+    let mut name = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((name, i + 1)),
+            '\\' => name.push(chars.next()?.1),
+            c => name.push(c),
+        }
+    }
+    None
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map(|(p, _)| *p)
+            .unwrap_or(0)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        match self.tokens.get(self.pos) {
+            Some((_, token)) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => Err(syntax_error(
+                self.position(),
+                "unexpected end of expression",
+            )),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(i)) if i.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut left = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut left = self.parse_unary()?;
+        while self.is_keyword("and") {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.is_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            let pos = self.position();
+            return match self.next()? {
+                Token::RParen => Ok(expr),
+                token => Err(syntax_error(
+                    pos,
+                    format!("expected ')', found {:?}", token),
+                )),
+            };
+        }
+
+        self.parse_predicate()
+    }
+
+    fn parse_field(&mut self) -> Result<Field> {
+        let pos = self.position();
+        match self.next()? {
+            Token::Path(path) => Ok(Field::Json(parse_path(pos, &path)?)),
+            Token::Ident(ident) => match ident.to_lowercase().as_str() {
+                "key" => Ok(Field::Key),
+                "value" | "payload" => Ok(Field::Value),
+                "partition" => Ok(Field::Partition),
+                "offset" => Ok(Field::Offset),
+                "timestamp" => Ok(Field::Timestamp),
+                "header" => {
+                    let name = match (self.next()?, self.next()?, self.next()?) {
+                        (Token::LParen, Token::Str(name), Token::RParen) => name,
+                        _ => return Err(syntax_error(pos, "expected header(\"name\")")),
+                    };
+                    Ok(Field::Header(name))
+                }
+                _ => Err(syntax_error(pos, format!("unknown field {}", ident))),
+            },
+            token => Err(syntax_error(
+                pos,
+                format!("expected field, found {:?}", token),
+            )),
+        }
+    }
+
+    fn parse_op(&mut self) -> Option<Op> {
+        let op = match self.peek()? {
+            Token::Op("==") => Op::Eq,
+            Token::Op("!=") => Op::Ne,
+            Token::Op(">") => Op::Gt,
+            Token::Op(">=") => Op::Ge,
+            Token::Op("<") => Op::Lt,
+            Token::Op("<=") => Op::Le,
+            Token::Op("~") => Op::Matches,
+            Token::Ident(i) if i.eq_ignore_ascii_case("contains") => Op::Contains,
+            Token::Ident(i) if i.eq_ignore_ascii_case("in") => Op::In,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(op)
+    }
+
+    fn parse_operand(&mut self, op: Op) -> Result<Operand> {
+        let pos = self.position();
+        let operand = match (op, self.next()?) {
+            (Op::Matches, Token::Str(s)) => Operand::Regex(
+                Regex::new(&s).map_err(|e| syntax_error(pos, format!("invalid regex: {}", e)))?,
+            ),
+            (Op::In, Token::Num(from)) => match (self.next()?, self.next()?) {
+                (Token::Range, Token::Num(to)) => Operand::Range(from, to),
+                _ => return Err(syntax_error(pos, "expected range like 10..20")),
+            },
+            (Op::Matches | Op::In, token) => {
+                return Err(syntax_error(pos, format!("unexpected {:?}", token)))
+            }
+            (_, Token::Str(s)) => Operand::Str(s),
+            (_, Token::Num(n)) => Operand::Num(n),
+            (_, Token::Ident(i)) if i == "true" => Operand::Bool(true),
+            (_, Token::Ident(i)) if i == "false" => Operand::Bool(false),
+            (_, Token::Ident(i)) if i == "null" => Operand::Null,
+            (_, token) => {
+                return Err(syntax_error(
+                    pos,
+                    format!("expected value, found {:?}", token),
+                ))
+            }
+        };
+        Ok(operand)
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expr> {
+        let pos = self.position();
+        let field = self.parse_field()?;
+        let op = match self.parse_op() {
+            Some(op) => op,
+            None => {
+                return match field {
+                    Field::Partition | Field::Offset | Field::Timestamp => {
+                        Err(syntax_error(pos, "comparison expected"))
+                    }
+                    _ => Ok(Expr::Exists(field)),
+                }
+            }
+        };
+
+        let operand = self.parse_operand(op)?;
+        let valid = match &field {
+            Field::Key | Field::Value | Field::Header(_) => {
+                matches!(
+                    (op, &operand),
+                    (Op::Eq | Op::Ne | Op::Contains, Operand::Str(_)) | (Op::Matches, _)
+                )
+            }
+            Field::Partition | Field::Offset | Field::Timestamp => {
+                matches!(
+                    (op, &operand),
+                    (
+                        Op::Eq | Op::Ne | Op::Gt | Op::Ge | Op::Lt | Op::Le,
+                        Operand::Num(_)
+                    ) | (Op::In, _)
+                )
+            }
+            Field::Json(_) => op != Op::In,
+        };
+
+        if valid {
+            Ok(Expr::Compare(field, op, operand))
+        } else {
+            Err(syntax_error(
+                pos,
+                format!("operator {:?} is not supported here", op),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kafka::MessageFilter;
+    use rdkafka::message::{OwnedHeaders, OwnedMessage, Timestamp};
+    use serde_json::json;
+
+    fn create_msg(key: &str, payload: &str, partition: i32, offset: i64) -> OwnedMessage {
+        OwnedMessage::new(
+            Some(payload.as_bytes().to_vec()),
+            Some(key.as_bytes().to_vec()),
+            "test".to_string(),
+            Timestamp::CreateTime(1650000000000),
+            partition,
+            offset,
+            Some(OwnedHeaders::new().add("type", "order")),
+        )
+    }
+
+    fn matches(filter: &str, msg: &OwnedMessage) -> bool {
+        MessageFilter::parse(filter).unwrap().matches(msg)
+    }
+
+    #[test]
+    fn test_fields() {
+        let msg = create_msg(
+            "k-17",
+            r#"{"id": "A-1", "items": [{"price": 12.5}], "tags": ["new"]}"#,
+            2,
+            150,
+        );
+
+        assert!(matches("", &msg));
+        assert!(matches("key == \"k-17\"", &msg));
+        assert!(matches("key contains \"17\" and not key == \"k\"", &msg));
+        assert!(matches("key ~ \"^k-\\d+$\"", &msg));
+        assert!(matches("value contains \"A-1\"", &msg));
+        assert!(matches("header(\"type\") == \"order\"", &msg));
+        assert!(!matches("header(\"trace\")", &msg));
+        assert!(matches("partition == 2 and offset in 100..200", &msg));
+        assert!(!matches("offset in 100..150", &msg));
+        assert!(matches("timestamp >= 1650000000000", &msg));
+        assert!(matches("$.id == \"A-1\" or partition == 0", &msg));
+        assert!(matches("$.items[0].price > 10", &msg));
+        assert!(matches("$.tags contains \"new\"", &msg));
+        assert!(matches("$[\"id\"] ~ \"^A\"", &msg));
+        assert!(!matches("$.missing", &msg));
+        assert!(matches("(partition == 1 or partition == 2) and $.id", &msg));
+    }
+
+    #[test]
+    fn test_paths() {
+        let msg = create_msg("k", r#"{"a b": {"x]y": "v", "c": 1}, "q\"": true}"#, 0, 0);
+        assert!(matches("$[\"a b\"][\"x]y\"] == \"v\"", &msg));
+        assert!(matches("$[\"a b\"].c == 1 and $[\"q\\\"\"]", &msg));
+        assert!(matches("($[\"a b\"].c>0)", &msg));
+
+        // Paths of decoded payloads
+        let msg = create_msg("k", "id=7", 0, 0);
+        let filter = MessageFilter::parse("$.id == 7").unwrap();
+        assert!(!filter.matches(&msg));
+        assert!(filter.matches_with(&msg, |_| Some(json!({"id": 7}))));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for filter in [
+            "key ==",
+            "key > \"a\"",
+            "offset contains \"1\"",
+            "partition",
+            "value ~ \"[\"",
+            "(key",
+            "key == \"a",
+            "unknown == 1",
+            "$.a[x] == 1",
+            "$[\"a b == 1",
+            "$[\"a\" == 1",
+            "key == \"a\" key",
+        ] {
+            assert!(MessageFilter::parse(filter).is_err(), "{}", filter);
+        }
+    }
+}
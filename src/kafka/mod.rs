@@ -1,22 +1,41 @@
 mod admin_ops;
+mod bulk_file;
 mod consumer_ops;
 mod kafka_ops;
+mod message_filter;
+mod partitioner;
 mod producer_ops;
+mod replay;
 
 pub use admin_ops::AdminCommand;
 pub use admin_ops::AdminOps;
+pub use bulk_file::{parse_records, BulkFormat};
+pub use consumer_ops::merge_by_timestamp;
+pub use consumer_ops::AbortedCheck;
+pub use consumer_ops::ConsumerCommand;
 pub use consumer_ops::ConsumerOps;
-pub use kafka_ops::{KafkaOps, Pref};
-pub use producer_ops::ProducerOps;
+pub use consumer_ops::{
+    OffsetChange, OffsetReset, PartitionLag, SearchEvent, SearchProgress, SearchStart,
+};
+pub use kafka_ops::{IsolationLevel, KafkaOps, Pref};
+pub use message_filter::MessageFilter;
+pub use partitioner::Partitioner;
+pub use producer_ops::{
+    BulkEvent, BulkMessage, BulkProgress, BulkSender, DeliveryContext, PendingDelivery,
+    ProducerCommand, ProducerOps,
+};
+pub use replay::{start_replay, HeaderRule, ReplayRange, ReplaySource, ReplayTransform};
 use sled::Tree;
 
 use anyhow::Result;
 use log::error;
+use openssl::provider::Provider;
 use openssl::rand::rand_bytes;
 use openssl::symm::{decrypt, encrypt, Cipher};
 use rdkafka::config::RDKafkaLogLevel;
 use rdkafka::message::{FromBytes, ToBytes};
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use thiserror::Error;
 
 /// Describes errors for kafka operations
@@ -26,6 +45,18 @@ pub enum KafkaOpsError {
     ClientNotOpen,
     #[error("server configuration for {0} not found")]
     ServerConfigNotFound(String),
+    #[error("offset {0} is out of range {1} - {2}")]
+    OffsetOutOfRange(i64, i64, i64),
+    #[error("no messages received at offset {0} of partition {1}")]
+    ReadStalled(i64, i32),
+    #[error("consumer group {0} has active members")]
+    GroupNotEmpty(String),
+    #[error("invalid filter at {0}: {1}")]
+    InvalidFilter(usize, String),
+    #[error("delivery report was not received")]
+    DeliveryLost,
+    #[error("{0} messages not delivered before flush timeout")]
+    FlushTimeout(i32),
     #[error("unknown kafka operations error")]
     Unknown,
 }
@@ -59,11 +90,19 @@ impl Password {
         Ok(buf)
     }
 
+    /// Blowfish cipher. OpenSSL 3 moved blowfish to the legacy provider, it is
+    /// loaded once and kept for the lifetime of the application
+    fn cipher() -> Cipher {
+        static LEGACY: OnceLock<Option<Provider>> = OnceLock::new();
+        LEGACY.get_or_init(|| Provider::try_load(None, "legacy", true).ok());
+        Cipher::bf_cfb64()
+    }
+
     /// Encrypt password using blowfish crypto algorithm from openssl library
     /// # Arguments
     ///    - password - string representation of password
     fn encrypt_password<T: AsRef<str>>(password: T) -> Result<Password> {
-        let cipher = Cipher::bf_cfb64();
+        let cipher = Password::cipher();
         let key = "master password";
         let mut key_bytes = vec![0; cipher.key_len()];
         for (dst, src) in key_bytes.iter_mut().zip(key.as_bytes()) {
@@ -88,7 +127,7 @@ impl Password {
 
     /// Decrypt password and return plain representation of password as array of bytes
     pub fn decrypt(&self) -> Result<Vec<u8>> {
-        let cipher = Cipher::bf_cfb64();
+        let cipher = Password::cipher();
         let key = "master password";
         let mut key_bytes = vec![0; cipher.key_len()];
         for (dst, src) in key_bytes.iter_mut().zip(key.as_bytes()) {
@@ -102,6 +141,20 @@ impl Password {
     }
 }
 
+/// Schema Registry connection parameters. Registry is not used when URL is empty
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SchemaRegistryConfig {
+    pub url: String,
+    /// User name for basic authentication, not used when empty
+    pub username: String,
+    pub password: Option<Password>,
+    /// CA certificate in PEM format used to verify the registry
+    pub ssl_ca_location: String,
+    /// Client keystore in PKCS#12 format
+    pub ssl_keystore_location: String,
+    pub ssl_keystore_password: Option<Password>,
+}
+
 /// Kafka server parameters
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KafkaServer {
@@ -113,6 +166,13 @@ pub struct KafkaServer {
     pub ssl_security_protocol: String,
     pub message_max_bytes: u64,
     pub fetch_max_bytes: u64,
+    #[serde(default)]
+    pub isolation_level: IsolationLevel,
+    /// Partitioner of messages produced without partition
+    #[serde(default)]
+    pub partitioner: Partitioner,
+    #[serde(default)]
+    pub schema_registry: SchemaRegistryConfig,
 }
 
 impl ToString for KafkaServer {
@@ -145,6 +205,9 @@ impl KafkaServer {
             ssl_security_protocol: "".to_string(),
             message_max_bytes: 0,
             fetch_max_bytes: 0,
+            isolation_level: IsolationLevel::default(),
+            partitioner: Partitioner::default(),
+            schema_registry: SchemaRegistryConfig::default(),
         }
     }
 
@@ -196,17 +259,27 @@ impl KafkaServer {
             ssl_security_protocol,
             message_max_bytes,
             fetch_max_bytes,
+            isolation_level: IsolationLevel::default(),
+            partitioner: Partitioner::default(),
+            schema_registry: SchemaRegistryConfig::default(),
         }
     }
 
     /// Opens connection to kafka and returns KafkaOps object
     pub fn open(&self) -> Result<KafkaOps> {
+        self.open_with_group("kators-g-1")
+    }
+
+    /// Opens connection to kafka using the given consumer group
+    pub fn open_with_group<T: AsRef<str>>(&self, group_id: T) -> Result<KafkaOps> {
         let mut builder = KafkaOps::builder()
             .with_prop("enable.auto.commit", "false")
             .with_bootstrap(&self.bootstrap)
             .with_ssl_cert_verification(self.ssl_verification)
             .with_security_protocol(&self.ssl_security_protocol)
-            .with_group_id("kators-g-1")
+            .with_group_id(group_id.as_ref())
+            .with_isolation_level(self.isolation_level)
+            .with_partitioner(self.partitioner)
             .with_message_max_bytes(self.message_max_bytes)
             .with_fetch_max_bytes(self.fetch_max_bytes);
 
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Partitioner of librdkafka choosing the partition of messages sent without one
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum Partitioner {
+    /// Murmur2 hash of the key, same partitions as the Java producer.
+    /// Messages without key are randomly partitioned
+    Murmur2Random,
+    /// CRC32 hash of the key, empty and missing keys are randomly partitioned.
+    /// Default of librdkafka
+    #[default]
+    ConsistentRandom,
+    /// Random partition for every message
+    Random,
+}
+
+impl Partitioner {
+    pub const ALL: [Partitioner; 3] = [
+        Partitioner::Murmur2Random,
+        Partitioner::ConsistentRandom,
+        Partitioner::Random,
+    ];
+
+    /// Value of the `partitioner` property
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Partitioner::Murmur2Random => "murmur2_random",
+            Partitioner::ConsistentRandom => "consistent_random",
+            Partitioner::Random => "random",
+        }
+    }
+
+    /// Returns partition the producer chooses for the key, `None` when the
+    /// partition is random
+    pub fn partition(&self, key: Option<&[u8]>, partitions: i32) -> Option<i32> {
+        if partitions <= 0 {
+            return None;
+        }
+        let hash = match (self, key) {
+            (Partitioner::Murmur2Random, Some(key)) => murmur2(key) & 0x7fffffff,
+            (Partitioner::ConsistentRandom, Some(key)) if !key.is_empty() => crc32(key),
+            _ => return None,
+        };
+        Some((hash % partitions as u32) as i32)
+    }
+}
+
+impl Display for Partitioner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Murmur2 hash used by the Java client and by librdkafka
+fn murmur2(data: &[u8]) -> u32 {
+    const SEED: u32 = 0x9747b28c;
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let mut h = SEED ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M) ^ k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate().rev() {
+            h ^= (*byte as u32) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h
+}
+
+/// CRC-32 (IEEE) checksum used by the consistent partitioners of librdkafka
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kafka::partitioner::{crc32, murmur2};
+    use crate::kafka::Partitioner;
+
+    #[test]
+    fn test_hashes() {
+        // Values of the Java client tests
+        for (key, expected) in [
+            ("21", -973932308),
+            ("foobar", -790332482),
+            ("a-little-bit-long-string", -985981536),
+            ("a-little-bit-longer-string", -1486304829),
+            (
+                "lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8",
+                -58897971,
+            ),
+            ("abc", 479470107),
+        ] {
+            assert_eq!(expected, murmur2(key.as_bytes()) as i32, "{}", key);
+        }
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+    }
+
+    #[test]
+    fn test_partition() {
+        let key = Some("foobar".as_bytes());
+        assert_eq!(
+            Some((-790332482i32 & 0x7fffffff) % 12),
+            Partitioner::Murmur2Random.partition(key, 12)
+        );
+        assert_eq!(
+            Some((0xcbf43926u32 % 5) as i32),
+            Partitioner::ConsistentRandom.partition(Some(b"123456789"), 5)
+        );
+        assert_eq!(Some(0), Partitioner::Murmur2Random.partition(key, 1));
+        assert_eq!(None, Partitioner::Murmur2Random.partition(None, 3));
+        assert_eq!(None, Partitioner::ConsistentRandom.partition(Some(b""), 3));
+        assert_eq!(None, Partitioner::Random.partition(key, 3));
+        assert_eq!(None, Partitioner::Murmur2Random.partition(key, 0));
+    }
+}
@@ -1,10 +1,126 @@
+use crate::kafka::KafkaOpsError;
 use anyhow::Result;
+use log::info;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
 use rdkafka::message::{OwnedHeaders, ToBytes};
-use rdkafka::producer::{BaseProducer, BaseRecord};
+use rdkafka::producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer};
+use rdkafka::{ClientContext, Message};
+use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter};
+use std::sync::mpsc;
+use std::sync::mpsc::{sync_channel, Receiver, Sender, SyncSender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
 
+/// Position of the message written by the broker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delivery {
+    pub partition: i32,
+    pub offset: i64,
+}
+
+type DeliverySender = SyncSender<Result<Delivery, KafkaError>>;
+
+/// Producer context passing delivery reports to the sender of each message
+pub struct DeliveryContext;
+
+impl ClientContext for DeliveryContext {}
+
+impl ProducerContext for DeliveryContext {
+    type DeliveryOpaque = Box<DeliverySender>;
+
+    fn delivery(&self, delivery_result: &DeliveryResult<'_>, sender: Self::DeliveryOpaque) {
+        let report = match delivery_result {
+            Ok(msg) => Ok(Delivery {
+                partition: msg.partition(),
+                offset: msg.offset(),
+            }),
+            Err((err, _)) => Err(err.clone()),
+        };
+        let _ = sender.send(report);
+    }
+}
+
+/// Message accepted by the producer queue and waiting for the delivery report
+#[derive(Debug)]
+pub struct PendingDelivery {
+    report: Receiver<Result<Delivery, KafkaError>>,
+}
+
+impl PendingDelivery {
+    /// Returns delivery result when the report is received, otherwise `None`
+    pub fn try_result(&self) -> Option<Result<Delivery>> {
+        match self.report.try_recv() {
+            Ok(report) => Some(report.map_err(|e| e.into())),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(KafkaOpsError::DeliveryLost.into())),
+        }
+    }
+}
+
+/// Message of the bulk produce with encoded key and payload
+#[derive(Debug, Clone)]
+pub struct BulkMessage {
+    /// Origin of the message like the line of the source file, used in error reports
+    pub source: String,
+    pub key: Option<Vec<u8>>,
+    pub payload: Option<Vec<u8>>,
+    pub headers: Vec<(String, Vec<u8>)>,
+    pub partition: Option<i32>,
+    /// Milliseconds since the epoch
+    pub timestamp: Option<i64>,
+}
+
+/// Counters of the bulk produce
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BulkProgress {
+    pub total: usize,
+    /// Messages accepted by the producer queue
+    pub sent: usize,
+    pub delivered: usize,
+    pub failed: usize,
+    /// Messages not sent because they can't be read or encoded
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+impl BulkProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total > 0 {
+            (self.delivered + self.failed + self.skipped) as f32 / self.total as f32
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Events sent by the bulk produce worker
+#[derive(Debug)]
+pub enum BulkEvent {
+    Progress(BulkProgress),
+    /// Origin of the message and the error
+    Error(String, String),
+    /// All messages are delivered or failed, or the worker is stopped
+    Finished(BulkProgress),
+}
+
+#[derive(Clone, Debug)]
+pub enum ProducerCommand {
+    Stop,
+}
+
+/// Delay before retrying a message rejected because the producer queue is full
+const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Minimal interval between progress events of the bulk produce
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Time to wait for delivery reports after the last message is sent
+const BULK_FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Producer with background thread serving delivery reports
 pub struct ProducerOps {
-    producer: BaseProducer,
+    producer: ThreadedProducer<DeliveryContext>,
 }
 
 impl Debug for ProducerOps {
@@ -14,28 +130,253 @@ impl Debug for ProducerOps {
 }
 
 impl ProducerOps {
-    pub fn new(producer: BaseProducer) -> Self {
+    pub fn new(producer: ThreadedProducer<DeliveryContext>) -> Self {
         Self { producer }
     }
 
-    pub fn send<T: AsRef<str>, K: ToBytes, P: ToBytes>(
+    /// Enqueues message to the partition of the topic, the configured partitioner chooses
+    /// the partition when `partition` is `None`. Message without key or payload is sent
+    /// when they are `None`, current time is used without `timestamp`. Broker result is
+    /// available from the returned pending delivery
+    pub fn send<T: AsRef<str>, K: ToBytes, P: ToBytes, V: AsRef<[u8]>>(
         &self,
         topic: T,
-        partition: i32,
-        key: &K,
-        payload: &P,
-        headers: Vec<(T, T)>,
-    ) -> Result<()> {
+        partition: Option<i32>,
+        key: Option<&K>,
+        payload: Option<&P>,
+        headers: Vec<(T, V)>,
+        timestamp: Option<i64>,
+    ) -> Result<PendingDelivery> {
         let owned_headers = headers.iter().fold(OwnedHeaders::new(), |hdr, (k, v)| {
             hdr.add(k.as_ref(), v.as_ref())
         });
 
-        let rec = BaseRecord::to(topic.as_ref())
-            .partition(partition)
-            .key(key)
-            .headers(owned_headers)
-            .payload(payload);
+        let (sender, report) = sync_channel(1);
+        let mut rec =
+            BaseRecord::with_opaque_to(topic.as_ref(), Box::new(sender)).headers(owned_headers);
+        if let Some(payload) = payload {
+            rec = rec.payload(payload);
+        }
+        if let Some(timestamp) = timestamp {
+            rec = rec.timestamp(timestamp);
+        }
+        if let Some(partition) = partition {
+            rec = rec.partition(partition);
+        }
+        if let Some(key) = key {
+            rec = rec.key(key);
+        }
+
+        self.producer.send(rec).map_err(|(e, _)| e)?;
+        Ok(PendingDelivery { report })
+    }
+
+    /// Waits until all enqueued messages are delivered or failed. Returns error
+    /// when some messages are still in flight after `timeout`
+    pub fn flush(&self, timeout: Duration) -> Result<()> {
+        self.producer.flush(timeout);
+        match self.producer.in_flight_count() {
+            0 => Ok(()),
+            count => Err(KafkaOpsError::FlushTimeout(count).into()),
+        }
+    }
+
+    /// Number of messages waiting to be sent or acknowledged by the brokers
+    pub fn in_flight_count(&self) -> i32 {
+        self.producer.in_flight_count()
+    }
+
+    /// Starts background worker sending messages to the topic with at most `rate` messages
+    /// per second, `rate` 0 means no limit. Messages are prepared in the worker by
+    /// `produce`, which passes them to the [BulkSender] in batches. Worker reports progress
+    /// and failed messages, it stops on the [ProducerCommand::Stop] command
+    pub fn start_bulk<T, F>(
+        self,
+        topic: T,
+        rate: u32,
+        produce: F,
+    ) -> (Sender<ProducerCommand>, Receiver<BulkEvent>)
+    where
+        T: AsRef<str>,
+        F: FnOnce(&mut BulkSender<'_>) + Send + 'static,
+    {
+        let topic = topic.as_ref().to_string();
+        let (cmd_tx, cmd_rx) = mpsc::channel::<ProducerCommand>();
+        let (event_tx, event_rx) = mpsc::channel::<BulkEvent>();
+        thread::spawn(move || {
+            info!("Start bulk produce worker");
+            let mut sender = BulkSender::new(&self, &topic, rate, &cmd_rx, &event_tx);
+            produce(&mut sender);
+            sender.finish();
+            info!("Finish bulk produce worker");
+        });
+
+        (cmd_tx, event_rx)
+    }
+}
+
+/// Bulk produce of one worker. Messages are sent in batches, progress and the rate
+/// limit cover all of them
+pub struct BulkSender<'a> {
+    producer: &'a ProducerOps,
+    topic: &'a str,
+    rate: u32,
+    commands: &'a Receiver<ProducerCommand>,
+    events: &'a Sender<BulkEvent>,
+    started: Instant,
+    last_progress: Instant,
+    progress: BulkProgress,
+    /// Messages passed to `send`, used by the rate limit
+    count: usize,
+    /// Sent messages waiting for delivery reports in order of sending
+    pending: VecDeque<(String, PendingDelivery)>,
+    stopped: bool,
+}
+
+impl<'a> BulkSender<'a> {
+    fn new(
+        producer: &'a ProducerOps,
+        topic: &'a str,
+        rate: u32,
+        commands: &'a Receiver<ProducerCommand>,
+        events: &'a Sender<BulkEvent>,
+    ) -> Self {
+        Self {
+            producer,
+            topic,
+            rate,
+            commands,
+            events,
+            started: Instant::now(),
+            last_progress: Instant::now(),
+            progress: BulkProgress::default(),
+            count: 0,
+            pending: VecDeque::new(),
+            stopped: false,
+        }
+    }
+
+    /// Adds messages to the total shown by the progress
+    pub fn expect(&mut self, count: usize) {
+        self.progress.total += count;
+    }
+
+    /// Removes expected messages which turned out to be missing
+    pub fn unexpect(&mut self, count: usize) {
+        self.progress.total = self.progress.total.saturating_sub(count);
+    }
+
+    /// Reports expected message which is not sent because it can't be read or encoded
+    pub fn skip(&mut self, source: String, error: String) {
+        self.progress.skipped += 1;
+        let _ = self.events.send(BulkEvent::Error(source, error));
+    }
+
+    /// Reports error which is not related to a single message
+    pub fn error(&self, source: String, error: String) {
+        let _ = self.events.send(BulkEvent::Error(source, error));
+    }
 
-        self.producer.send(rec).map_err(|(e, _)| e.into())
+    /// Returns `true` when the worker is stopped, checks commands first
+    pub fn is_stopped(&mut self) -> bool {
+        if !self.stopped && !matches!(self.commands.try_recv(), Err(TryRecvError::Empty)) {
+            self.stopped = true;
+        }
+        self.stopped
     }
+
+    /// Sends messages in order. Returns `false` when the worker is stopped
+    pub fn send(&mut self, messages: &[BulkMessage]) -> bool {
+        for msg in messages {
+            if self.rate > 0 {
+                let due =
+                    self.started + Duration::from_secs_f64(self.count as f64 / self.rate as f64);
+                if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                    thread::sleep(wait);
+                }
+            }
+            self.count += 1;
+            loop {
+                if self.is_stopped() {
+                    return false;
+                }
+                let headers = msg
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_slice()))
+                    .collect();
+                match self.producer.send(
+                    self.topic,
+                    msg.partition,
+                    msg.key.as_ref(),
+                    msg.payload.as_ref(),
+                    headers,
+                    msg.timestamp,
+                ) {
+                    Ok(delivery) => {
+                        self.progress.sent += 1;
+                        self.pending.push_back((msg.source.clone(), delivery));
+                        break;
+                    }
+                    Err(err) if is_queue_full(&err) => {
+                        thread::sleep(QUEUE_FULL_BACKOFF);
+                        self.receive();
+                    }
+                    Err(err) => {
+                        self.progress.failed += 1;
+                        self.error(msg.source.clone(), err.to_string());
+                        break;
+                    }
+                }
+            }
+            self.receive();
+        }
+        true
+    }
+
+    /// Reports received deliveries, reports of later messages are taken when
+    /// all earlier ones are received
+    fn receive(&mut self) {
+        while let Some((source, delivery)) = self.pending.front() {
+            match delivery.try_result() {
+                None => break,
+                Some(Ok(_)) => self.progress.delivered += 1,
+                Some(Err(err)) => {
+                    self.progress.failed += 1;
+                    self.error(source.clone(), err.to_string());
+                }
+            }
+            self.pending.pop_front();
+        }
+        self.progress.elapsed = self.started.elapsed();
+        if self.last_progress.elapsed() >= PROGRESS_INTERVAL {
+            self.last_progress = Instant::now();
+            let _ = self.events.send(BulkEvent::Progress(self.progress.clone()));
+        }
+    }
+
+    /// Waits for delivery reports of sent messages unless the worker is stopped
+    /// and sends [BulkEvent::Finished]
+    fn finish(mut self) {
+        let flushed = if self.stopped {
+            Ok(())
+        } else {
+            self.producer.flush(BULK_FLUSH_TIMEOUT)
+        };
+        self.receive();
+        if let Err(err) = flushed {
+            for (source, _) in self.pending.drain(..) {
+                self.progress.failed += 1;
+                let _ = self.events.send(BulkEvent::Error(source, err.to_string()));
+            }
+        }
+        let _ = self.events.send(BulkEvent::Finished(self.progress));
+    }
+}
+
+fn is_queue_full(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<KafkaError>(),
+        Some(KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull))
+    )
 }
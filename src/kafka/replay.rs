@@ -0,0 +1,268 @@
+use crate::kafka::{
+    BulkEvent, BulkMessage, BulkSender, ConsumerOps, KafkaOpsError, ProducerCommand, ProducerOps,
+};
+use anyhow::Result;
+use rdkafka::message::{Headers, OwnedMessage};
+use rdkafka::Message;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::Duration;
+
+/// Maximum time without any progress while reading a source partition
+const READ_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of source messages read before they are sent
+const READ_BATCH: usize = 1000;
+
+/// Messages of the source partitions to replay
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayRange {
+    /// Offsets from the first one to the end, the end is excluded.
+    /// Offsets are limited by the watermarks of each partition
+    Offsets(i64, i64),
+    /// Messages with timestamps from the first one to the end, the end is excluded.
+    /// Milliseconds since the epoch
+    Time(i64, i64),
+}
+
+/// Partitions of the topic where replayed messages are read
+#[derive(Clone, Debug)]
+pub struct ReplaySource {
+    pub topic: String,
+    pub partitions: Vec<i32>,
+    pub range: ReplayRange,
+}
+
+/// Change of headers of replayed messages
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderRule {
+    /// Replaces the header value or adds the header
+    Set(String, String),
+    /// Removes all headers with the name
+    Remove(String),
+}
+
+/// Describes how the source message is turned into the target one
+#[derive(Clone, Debug)]
+pub struct ReplayTransform {
+    /// Target topic name, `{topic}` is replaced by the source topic name
+    pub topic: String,
+    /// Sends to the source partition, otherwise the partitioner chooses the partition
+    pub keep_partition: bool,
+    /// Sends with the source timestamp, otherwise the current time is used
+    pub keep_timestamp: bool,
+    pub headers: Vec<HeaderRule>,
+}
+
+impl ReplayTransform {
+    pub fn target_topic(&self, source_topic: &str) -> String {
+        self.topic.replace("{topic}", source_topic)
+    }
+
+    /// Copies key, payload and headers of the message and applies header rules
+    pub fn apply(&self, msg: &OwnedMessage) -> BulkMessage {
+        let mut headers = vec![];
+        if let Some(source) = msg.headers() {
+            for i in 0..source.count() {
+                if let Some((name, value)) = source.get(i) {
+                    headers.push((name.to_string(), value.to_vec()));
+                }
+            }
+        }
+        for rule in &self.headers {
+            match rule {
+                HeaderRule::Set(name, value) => {
+                    headers.retain(|(n, _)| n != name);
+                    headers.push((name.clone(), value.as_bytes().to_vec()));
+                }
+                HeaderRule::Remove(name) => headers.retain(|(n, _)| n != name),
+            }
+        }
+
+        BulkMessage {
+            source: format!("partition {} offset {}", msg.partition(), msg.offset()),
+            key: msg.key().map(|key| key.to_vec()),
+            payload: msg.payload().map(|payload| payload.to_vec()),
+            headers,
+            partition: self.keep_partition.then(|| msg.partition()),
+            timestamp: if self.keep_timestamp {
+                msg.timestamp().to_millis()
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Returns offsets `[start, end)` of the range in the source partition. The range is
+/// clamped to the watermarks of the consumer, the high one is the last stable offset for
+/// read_committed consumers, so messages of open transactions are not waited for
+fn partition_offsets(
+    consumer: &ConsumerOps,
+    topic: &str,
+    partition: i32,
+    range: &ReplayRange,
+) -> Result<(i64, i64)> {
+    let (low, high) = consumer.read_watermarks(topic, partition)?;
+    let (start, end) = match range {
+        ReplayRange::Offsets(start, end) => (*start, *end),
+        ReplayRange::Time(from, to) => (
+            consumer
+                .offset_for_timestamp(topic, partition, *from)?
+                .unwrap_or(high),
+            consumer
+                .offset_for_timestamp(topic, partition, *to)?
+                .unwrap_or(high),
+        ),
+    };
+    let start = start.clamp(low, high);
+    Ok((start, end.clamp(start, high)))
+}
+
+/// Reads offsets `[start, end)` of the source partition in batches and sends each batch
+/// before reading the next one. Offsets without sent messages are removed from the total
+fn replay_partition(
+    sender: &mut BulkSender<'_>,
+    consumer: &ConsumerOps,
+    source: &ReplaySource,
+    partition: i32,
+    (start, end): (i64, i64),
+    transform: &ReplayTransform,
+) {
+    let mut position = start;
+    while position < end && !sender.is_stopped() {
+        let read = match consumer.read_range(
+            &source.topic,
+            partition,
+            position,
+            end,
+            READ_BATCH,
+            READ_IDLE_TIMEOUT,
+        ) {
+            Ok(read) => read,
+            Err(err) => {
+                sender.error(format!("partition {}", partition), err.to_string());
+                break;
+            }
+        };
+        let messages = read
+            .messages
+            .iter()
+            // Timestamps are set by producers and may be out of order
+            .filter(|msg| match &source.range {
+                ReplayRange::Time(from, to) => msg
+                    .timestamp()
+                    .to_millis()
+                    .is_some_and(|ts| ts >= *from && ts < *to),
+                ReplayRange::Offsets(_, _) => true,
+            })
+            .map(|msg| transform.apply(msg))
+            .collect::<Vec<BulkMessage>>();
+        // Messages read before a stall are sent too
+        sender.send(&messages);
+        // Control records, compacted and filtered out messages
+        sender.unexpect((read.position - position) as usize - messages.len());
+        let progress = read.position > position;
+        position = read.position;
+        if read.stalled {
+            let stall = KafkaOpsError::ReadStalled(position, partition);
+            sender.error(format!("partition {}", partition), stall.to_string());
+            break;
+        }
+        // The partition ends before the range end, e.g. after a leader change
+        if !progress {
+            break;
+        }
+    }
+    sender.unexpect((end - position) as usize);
+}
+
+/// Copies messages of the source partitions to the target topic in the background
+/// worker like [ProducerOps::start_bulk], the producer may be connected to another
+/// server. Partitions are read in order, batches of messages are sent as they are read.
+/// Worker stops on the [ProducerCommand::Stop] command
+pub fn start_replay(
+    consumer: ConsumerOps,
+    producer: ProducerOps,
+    source: ReplaySource,
+    transform: ReplayTransform,
+    rate: u32,
+) -> (Sender<ProducerCommand>, Receiver<BulkEvent>) {
+    let topic = transform.target_topic(&source.topic);
+    producer.start_bulk(topic, rate, move |sender| {
+        // Offset ranges give the expected total, it is reduced by missing messages
+        let mut ranges = vec![];
+        for partition in &source.partitions {
+            match partition_offsets(&consumer, &source.topic, *partition, &source.range) {
+                Ok((start, end)) => {
+                    sender.expect((end - start) as usize);
+                    ranges.push((*partition, (start, end)));
+                }
+                Err(err) => sender.error(format!("partition {}", partition), err.to_string()),
+            }
+        }
+
+        for (partition, offsets) in ranges {
+            if sender.is_stopped() {
+                break;
+            }
+            replay_partition(sender, &consumer, &source, partition, offsets, &transform);
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kafka::{HeaderRule, ReplayTransform};
+    use rdkafka::message::{OwnedHeaders, OwnedMessage, Timestamp};
+
+    #[test]
+    fn test_transform() {
+        let msg = OwnedMessage::new(
+            Some(b"payload".to_vec()),
+            Some(b"key".to_vec()),
+            "orders".to_string(),
+            Timestamp::CreateTime(1700000000000),
+            3,
+            42,
+            Some(
+                OwnedHeaders::new()
+                    .add("trace-id", "t1")
+                    .add("type", &[0u8, 1u8][..])
+                    .add("trace-id", "t2")
+                    .add("source", "a"),
+            ),
+        );
+        let mut transform = ReplayTransform {
+            topic: "{topic}-copy".to_string(),
+            keep_partition: true,
+            keep_timestamp: true,
+            headers: vec![
+                HeaderRule::Remove("trace-id".to_string()),
+                HeaderRule::Set("source".to_string(), "replay".to_string()),
+            ],
+        };
+        assert_eq!("orders-copy", transform.target_topic("orders"));
+
+        let copy = transform.apply(&msg);
+        assert_eq!("partition 3 offset 42", copy.source);
+        assert_eq!(Some(b"key".to_vec()), copy.key);
+        assert_eq!(Some(b"payload".to_vec()), copy.payload);
+        assert_eq!(
+            vec![
+                ("type".to_string(), vec![0u8, 1u8]),
+                ("source".to_string(), b"replay".to_vec())
+            ],
+            copy.headers
+        );
+        assert_eq!(Some(3), copy.partition);
+        assert_eq!(Some(1700000000000), copy.timestamp);
+
+        transform.keep_partition = false;
+        transform.keep_timestamp = false;
+        transform.headers.clear();
+        let copy = transform.apply(&msg);
+        assert_eq!(4, copy.headers.len());
+        assert_eq!(None, copy.partition);
+        assert_eq!(None, copy.timestamp);
+    }
+}